@@ -9,6 +9,8 @@ use syn::spanned::Spanned;
 /// 那么就要这样写：
 /// #[proc_macro_derive(Builder, attributes(builder))]
 ///  
+/// 结构体上标注#[builder(traits)]时，生成的代码会实现builder_taint中的Builder、Buildable与DynBuilder，
+/// 此时使用方需要依赖builder_taint，不标注则生成的代码不引用builder_taint
 ///
 #[proc_macro_derive(Builder, attributes(builder))]
 pub fn derive(input: TokenStream) -> TokenStream {
@@ -21,6 +23,39 @@ pub fn derive(input: TokenStream) -> TokenStream {
     }
 }
 
+/// 函数上的属性宏
+/// 参数很多的函数可以通过具名参数的Builder来调用，避免位置参数传错
+/// #[builder]
+/// fn spawn(exe: String, #[builder(each = "arg")] args: Vec<String>, cwd: Option<String>) {}
+/// 会生成SpawnBuilder以及spawn_builder()，最后通过call()调用原函数
+/// 方法需要在所在的impl块上同样标注#[builder]，生成的入口为Type::method_builder()
+#[proc_macro_attribute]
+pub fn builder(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let attr = proc_macro2::TokenStream::from(attr);
+    let item = syn::parse_macro_input!(item as syn::Item);
+
+    let ret = if !attr.is_empty() {
+        Err(syn::Error::new_spanned(
+            attr,
+            "`#[builder]` on functions does not take arguments",
+        ))
+    } else {
+        match item {
+            syn::Item::Fn(item_fn) => do_expand_fn(item_fn),
+            syn::Item::Impl(item_impl) => do_expand_impl(item_impl),
+            other => Err(syn::Error::new_spanned(
+                other,
+                "`#[builder]` can only be applied to a function or an impl block",
+            )),
+        }
+    };
+
+    match ret {
+        Ok(tokenstream) => tokenstream.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
 type StructFields = syn::punctuated::Punctuated<syn::Field, syn::Token!(,)>;
 
 fn get_fields_from_derive_input(st: &syn::DeriveInput) -> syn::Result<&StructFields> {
@@ -40,20 +75,22 @@ fn get_fields_from_derive_input(st: &syn::DeriveInput) -> syn::Result<&StructFie
 
 fn generate_builder_struct_fields_def(
     fields: &StructFields,
+    options: &BuilderOptions,
 ) -> syn::Result<proc_macro2::TokenStream> {
     let idents: Vec<_> = fields.iter().map(|f| &f.ident).collect();
+    let option_path = options.option_path();
 
     let types: syn::Result<Vec<proc_macro2::TokenStream>> = fields
         .iter()
         .map(|f| {
             if let Some(inner_ty) = get_generic_inner_type(&f.ty, "Option") {
-                Ok(quote::quote! (std::option::Option<#inner_ty>))
+                Ok(quote::quote! (#option_path<#inner_ty>))
             } else if get_user_specified_attribute_ident_for_vec(f)?.is_some() {
                 let origin_ty = &f.ty;
                 Ok(quote::quote! (#origin_ty))
             } else {
                 let inner_ty = &f.ty;
-                Ok(quote::quote! (std::option::Option<#inner_ty>))
+                Ok(quote::quote! (#option_path<#inner_ty>))
             }
         })
         .collect();
@@ -66,39 +103,56 @@ fn generate_builder_struct_fields_def(
 
 fn generate_builder_struct_factory_init_clauses(
     fields: &StructFields,
+    options: &BuilderOptions,
 ) -> syn::Result<Vec<proc_macro2::TokenStream>> {
-    let init_clauses: syn::Result<Vec<proc_macro2::TokenStream>> = fields
+    let option_path = options.option_path();
+    let vec_path = options.vec_path();
+
+    fields
         .iter()
         .map(|f| {
             let ident = &f.ident;
 
             if get_user_specified_attribute_ident_for_vec(f)?.is_some() {
                 Ok(quote::quote! {
-                    #ident : std::vec::Vec::new(),
+                    #ident : #vec_path::new(),
                 })
             } else {
                 Ok(quote::quote! {
-                    #ident : std::option::Option::None,
+                    #ident : #option_path::None,
                 })
             }
         })
-        .collect();
-
-    Ok(init_clauses?)
+        .collect()
 }
 
-fn generate_setter_functions(fields: &StructFields) -> syn::Result<proc_macro2::TokenStream> {
+// setter的可见性与被构建的结构体或函数保持一致
+fn generate_setter_functions(
+    fields: &StructFields,
+    vis: &syn::Visibility,
+    options: &BuilderOptions,
+) -> syn::Result<proc_macro2::TokenStream> {
     let idents: Vec<_> = fields.iter().map(|f| &f.ident).collect();
+    let option_path = options.option_path();
     let types: Vec<_> = fields.iter().map(|f| &f.ty).collect();
 
     let mut final_tokenstream = proc_macro2::TokenStream::new();
 
     for (idx, (ident, type_)) in idents.iter().zip(types.iter()).enumerate() {
+        let docs = get_field_doc_attrs(&fields[idx]);
+        let setter_doc = if docs.is_empty() {
+            let doc = format!("Sets the `{}` field.", ident.as_ref().unwrap());
+            quote::quote!(#[doc = #doc])
+        } else {
+            quote::quote!(#(#docs)*)
+        };
+
         let mut tokenstream_piece;
         if let Some(inner_ty) = get_generic_inner_type(type_, "Option") {
             tokenstream_piece = quote::quote! {
-                fn #ident(&mut self,#ident : #inner_ty)->&mut Self{
-                    self.#ident = std::option::Option::Some(#ident);
+                #setter_doc
+                #vis fn #ident(&mut self,#ident : #inner_ty)->&mut Self{
+                    self.#ident = #option_path::Some(#ident);
                     self
                 }
             };
@@ -110,8 +164,19 @@ fn generate_setter_functions(fields: &StructFields) -> syn::Result<proc_macro2::
                 "each field must be specified with Vec field",
             ))?;
 
+            let each_doc = if docs.is_empty() {
+                let doc = format!(
+                    "Appends one element to the `{}` field.",
+                    ident.as_ref().unwrap()
+                );
+                quote::quote!(#[doc = #doc])
+            } else {
+                quote::quote!(#(#docs)*)
+            };
+
             tokenstream_piece = quote::quote! {
-                fn #use_specified_ident(&mut self, #use_specified_ident : #inner_ty)->&mut Self{
+                #each_doc
+                #vis fn #use_specified_ident(&mut self, #use_specified_ident : #inner_ty)->&mut Self{
                     self.#ident.push(#use_specified_ident);
                     self
                 }
@@ -119,7 +184,8 @@ fn generate_setter_functions(fields: &StructFields) -> syn::Result<proc_macro2::
 
             if use_specified_ident != ident.as_ref().unwrap() {
                 tokenstream_piece.extend(quote::quote! {
-                fn #ident(&mut self,#ident:#type_)->&mut Self{
+                #setter_doc
+                #vis fn #ident(&mut self,#ident:#type_)->&mut Self{
                    self.#ident = #ident.clone();
                    self
                        }
@@ -127,8 +193,9 @@ fn generate_setter_functions(fields: &StructFields) -> syn::Result<proc_macro2::
             }
         } else {
             tokenstream_piece = quote::quote! {
-                fn #ident(&mut self,#ident : #type_)->&mut Self{
-                    self.#ident = std::option::Option::Some(#ident);
+                #setter_doc
+                #vis fn #ident(&mut self,#ident : #type_)->&mut Self{
+                    self.#ident = #option_path::Some(#ident);
                     self
                 }
             };
@@ -139,61 +206,498 @@ fn generate_setter_functions(fields: &StructFields) -> syn::Result<proc_macro2::
     Ok(final_tokenstream)
 }
 
-fn generate_build_function(
-    fields: &StructFields,
-    origin_struct_ident: &syn::Ident,
-) -> syn::Result<proc_macro2::TokenStream> {
-    let idents: Vec<_> = fields.iter().map(|f| &f.ident).collect();
+// 字段上的///文档注释，生成setter时原样带上
+fn get_field_doc_attrs(field: &syn::Field) -> Vec<&syn::Attribute> {
+    field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("doc"))
+        .collect()
+}
 
-    let types: Vec<_> = fields.iter().map(|f| &f.ty).collect();
+// 生成build()/call()的文档，列出所有必填字段，提醒调用方哪些字段必须设置
+fn generate_required_fields_doc(fields: &StructFields, summary: &str) -> syn::Result<String> {
+    let mut required = Vec::new();
+    for field in fields.iter() {
+        if is_required_field(field)? {
+            required.push(format!("`{}`", field.ident.as_ref().unwrap()));
+        }
+    }
+
+    let errors = if required.is_empty() {
+        "Every field is optional, so this never fails.".to_string()
+    } else {
+        format!(
+            "Returns an error if any of the required fields has not been set: {}.",
+            required.join(", ")
+        )
+    };
+    Ok(format!("{}\n\n{}", summary, errors))
+}
+
+// &'a T 这样的共享引用
+fn is_shared_reference(ty: &syn::Type) -> bool {
+    matches!(
+        ty,
+        syn::Type::Reference(syn::TypeReference {
+            mutability: None,
+            ..
+        })
+    )
+}
 
+// build()会clone共享引用以外的字段，所以这些字段类型中用到的类型参数都需要实现Clone，
+// 返回加上这些约束后的泛型参数，只用于build()所在的impl块，setter不受影响
+fn generate_build_generics(fields: &StructFields, generics: &syn::Generics) -> syn::Generics {
+    let mut build_generics = generics.clone();
+    let cloned_types: Vec<_> = fields
+        .iter()
+        .map(|field| &field.ty)
+        .filter(|ty| !is_shared_reference(get_generic_inner_type(ty, "Option").unwrap_or(ty)))
+        .collect();
+    let cloned_params: Vec<_> = generics
+        .type_params()
+        .map(|param| &param.ident)
+        .filter(|ident| cloned_types.iter().any(|ty| type_mentions_ident(ty, ident)))
+        .collect();
+    if !cloned_params.is_empty() {
+        let where_clause = build_generics.make_where_clause();
+        for ident in cloned_params {
+            where_clause
+                .predicates
+                .push(syn::parse_quote!(#ident: ::core::clone::Clone));
+        }
+    }
+    build_generics
+}
+
+// 类型中是否出现了名为ident的标识符，用于判断字段类型是否用到了某个类型参数
+fn type_mentions_ident(ty: &syn::Type, ident: &syn::Ident) -> bool {
+    fn mentions(tokens: proc_macro2::TokenStream, ident: &syn::Ident) -> bool {
+        tokens.into_iter().any(|tt| match tt {
+            proc_macro2::TokenTree::Ident(ref i) => i == ident,
+            proc_macro2::TokenTree::Group(group) => mentions(group.stream(), ident),
+            _ => false,
+        })
+    }
+
+    mentions(quote::quote!(#ty), ident)
+}
+
+// 既不是Option也没有指定each的字段必须由调用方设置
+fn is_required_field(field: &syn::Field) -> syn::Result<bool> {
+    Ok(get_generic_inner_type(&field.ty, "Option").is_none()
+        && get_user_specified_attribute_ident_for_vec(field)?.is_none())
+}
+
+// 生成必填字段的检查代码，以及每个字段最终取值的表达式
+// build()、build_and_reset()与函数Builder的call()共用这套逻辑
+// take_values为true时把值从Builder中取走，参数不需要实现Clone，Builder随之回到初始状态
+fn generate_build_checks_and_values(
+    fields: &StructFields,
+    options: &BuilderOptions,
+    builder_name_ident: &syn::Ident,
+    take_values: bool,
+) -> syn::Result<(Vec<proc_macro2::TokenStream>, Vec<proc_macro2::TokenStream>)> {
+    let result_path = options.result_path();
+    let error_ident = get_builder_error_ident(builder_name_ident);
     let mut checker_code_pieces = Vec::new();
-    let mut fill_result_clauses = Vec::new();
+    let mut value_exprs = Vec::new();
 
-    for idx in 0..idents.len() {
-        let ident = idents[idx];
+    for field in fields.iter() {
+        let ident = &field.ident;
 
-        if get_generic_inner_type(types[idx], "Option").is_none()
-            && get_user_specified_attribute_ident_for_vec(&fields[idx])?.is_none()
-        {
-            checker_code_pieces.push(quote::quote! {
-                if self.#ident.is_none(){
-                    let err = format!("{} field missing",stringify!(#ident));
-                    return std::result::Result::Err(err.into());
-                }
-            });
+        if is_required_field(field)? {
+            if options.no_std {
+                checker_code_pieces.push(quote::quote! {
+                    if self.#ident.is_none(){
+                        return #result_path::Err(#error_ident {
+                            missing_field: stringify!(#ident),
+                        });
+                    }
+                });
+            } else {
+                checker_code_pieces.push(quote::quote! {
+                    if self.#ident.is_none(){
+                        let err = format!("{} field missing",stringify!(#ident));
+                        return #result_path::Err(err.into());
+                    }
+                });
+            }
         }
 
-        if get_user_specified_attribute_ident_for_vec(&fields[idx])?.is_some() {
-            fill_result_clauses.push(quote::quote! {
-                #ident : self.#ident.clone(),
-            });
-        } else if get_generic_inner_type(types[idx], "Option").is_none() {
-            fill_result_clauses.push(quote::quote! {
-                #ident : self.#ident.clone().unwrap(),
+        if take_values {
+            if is_required_field(field)? {
+                value_exprs.push(quote::quote! {
+                    self.#ident.take().unwrap()
+                });
+            } else if get_user_specified_attribute_ident_for_vec(field)?.is_some() {
+                value_exprs.push(quote::quote! {
+                    ::core::mem::take(&mut self.#ident)
+                });
+            } else {
+                value_exprs.push(quote::quote! {
+                    self.#ident.take()
+                });
+            }
+            continue;
+        }
+
+        // 共享引用本身就是Copy的，直接复制引用，不需要clone被引用的数据
+        let is_reference =
+            is_shared_reference(get_generic_inner_type(&field.ty, "Option").unwrap_or(&field.ty));
+        if is_required_field(field)? {
+            if is_reference {
+                value_exprs.push(quote::quote! {
+                    self.#ident.unwrap()
+                });
+            } else {
+                value_exprs.push(quote::quote! {
+                    self.#ident.clone().unwrap()
+                });
+            }
+        } else if is_reference {
+            value_exprs.push(quote::quote! {
+                self.#ident
             });
         } else {
-            fill_result_clauses.push(quote::quote! {
-                #ident : self.#ident.clone(),
+            value_exprs.push(quote::quote! {
+                self.#ident.clone()
             });
         }
     }
 
+    Ok((checker_code_pieces, value_exprs))
+}
+
+// take_values为false时生成build()，clone已设置的值；为true时生成build_and_reset()，
+// 把值从Builder中取走，不需要Clone也不会复制Vec等拥有的数据
+fn generate_build_function(
+    fields: &StructFields,
+    origin_struct_ident: &syn::Ident,
+    builder_name_ident: &syn::Ident,
+    generics: &syn::Generics,
+    options: &BuilderOptions,
+    take_values: bool,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let (_, ty_generics, _) = generics.split_for_impl();
+    let idents: Vec<_> = fields.iter().map(|f| &f.ident).collect();
+    let (checker_code_pieces, value_exprs) =
+        generate_build_checks_and_values(fields, options, builder_name_ident, take_values)?;
+    let result_path = options.result_path();
+    let error_ty = options.error_type(builder_name_ident);
+    let (fn_ident, summary) = if take_values {
+        (
+            quote::format_ident!("build_and_reset"),
+            format!(
+                "Builds a `{}` by moving the values set so far out of the builder.\n\n\
+                 Nothing is cloned, so the builder is left empty and has to be filled in \
+                 again before building another value.",
+                origin_struct_ident
+            ),
+        )
+    } else {
+        (
+            quote::format_ident!("build"),
+            format!(
+                "Builds a `{}` from the values set so far.",
+                origin_struct_ident
+            ),
+        )
+    };
+    let build_doc = generate_required_fields_doc(fields, &summary)?;
+
     let token_stream = quote::quote! {
-        pub fn build(&mut self)->std::result::Result<#origin_struct_ident,std::boxed::Box<dyn std::error::Error>>{
+        #[doc = #build_doc]
+        pub fn #fn_ident(&mut self)->#result_path<#origin_struct_ident #ty_generics,#error_ty>{
              #(#checker_code_pieces)*
 
             let ret = #origin_struct_ident {
-                #(#fill_result_clauses)*
+                #(#idents : #value_exprs,)*
             };
 
-            std::result::Result::Ok(ret)
+            #result_path::Ok(ret)
         }
     };
 
     Ok(token_stream)
 }
 
+// 生成只接收必填字段的构造函数，返回的Builder中可选字段保持未设置
+fn generate_required_constructor(
+    fields: &StructFields,
+    builder_name_ident: &syn::Ident,
+    generics: &syn::Generics,
+    options: &BuilderOptions,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let (_, ty_generics, _) = generics.split_for_impl();
+    let option_path = options.option_path();
+    let mut idents = Vec::new();
+    let mut types = Vec::new();
+    for field in fields.iter() {
+        if is_required_field(field)? {
+            idents.push(&field.ident);
+            types.push(&field.ty);
+        }
+    }
+
+    let constructor_doc = format!(
+        "Creates a `{}` with every required field set, leaving the optional fields unset.",
+        builder_name_ident
+    );
+
+    Ok(quote::quote! {
+        #[doc = #constructor_doc]
+        pub fn new_required(#(#idents : #types),*)-> #builder_name_ident #ty_generics {
+            let mut builder = Self::builder();
+            #(builder.#idents = #option_path::Some(#idents);)*
+            builder
+        }
+    })
+}
+
+// 结构体上#[builder(...)]属性中的选项
+#[derive(Default)]
+struct BuilderOptions {
+    // #[builder(constructor)]，生成new_required构造函数
+    constructor: bool,
+    // #[builder(no_std)]，生成的代码只使用core和alloc中的类型
+    no_std: bool,
+    // #[builder(traits)]，实现builder_taint中的trait，需要使用方依赖builder_taint
+    traits: bool,
+}
+
+impl BuilderOptions {
+    fn option_path(&self) -> proc_macro2::TokenStream {
+        if self.no_std {
+            quote::quote!(::core::option::Option)
+        } else {
+            quote::quote!(std::option::Option)
+        }
+    }
+
+    fn vec_path(&self) -> proc_macro2::TokenStream {
+        if self.no_std {
+            quote::quote!(::alloc::vec::Vec)
+        } else {
+            quote::quote!(std::vec::Vec)
+        }
+    }
+
+    fn result_path(&self) -> proc_macro2::TokenStream {
+        if self.no_std {
+            quote::quote!(::core::result::Result)
+        } else {
+            quote::quote!(std::result::Result)
+        }
+    }
+
+    fn box_path(&self) -> proc_macro2::TokenStream {
+        if self.no_std {
+            quote::quote!(::alloc::boxed::Box)
+        } else {
+            quote::quote!(std::boxed::Box)
+        }
+    }
+
+    fn any_path(&self) -> proc_macro2::TokenStream {
+        if self.no_std {
+            quote::quote!(::core::any::Any)
+        } else {
+            quote::quote!(std::any::Any)
+        }
+    }
+
+    // no_std下没有std::error::Error，改用生成的只实现了Display的错误类型
+    fn error_type(&self, builder_name_ident: &syn::Ident) -> proc_macro2::TokenStream {
+        if self.no_std {
+            let error_ident = get_builder_error_ident(builder_name_ident);
+            quote::quote!(#error_ident)
+        } else {
+            quote::quote!(std::boxed::Box<dyn std::error::Error>)
+        }
+    }
+}
+
+// CommandBuilder => CommandBuilderError
+fn get_builder_error_ident(builder_name_ident: &syn::Ident) -> syn::Ident {
+    syn::Ident::new(
+        &format!("{}Error", builder_name_ident),
+        builder_name_ident.span(),
+    )
+}
+
+// no_std模式下build()返回的错误类型
+fn generate_builder_error_def(builder_name_ident: &syn::Ident) -> proc_macro2::TokenStream {
+    let error_ident = get_builder_error_ident(builder_name_ident);
+
+    let error_doc = format!("Error returned by `{}::build`.", builder_name_ident);
+
+    quote::quote! {
+        #[doc = #error_doc]
+        #[derive(Debug)]
+        pub struct #error_ident {
+            /// Name of the required field that was not set.
+            pub missing_field: &'static str,
+        }
+
+        impl ::core::fmt::Display for #error_ident {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                ::core::write!(f, "{} field missing", self.missing_field)
+            }
+        }
+    }
+}
+
+fn get_struct_builder_options(st: &syn::DeriveInput) -> syn::Result<BuilderOptions> {
+    let mut options = BuilderOptions::default();
+
+    for attr in st.attrs.iter().filter(|attr| attr.path.is_ident("builder")) {
+        if let syn::Meta::List(syn::MetaList { ref nested, .. }) = attr.parse_meta()? {
+            for meta in nested.iter() {
+                match meta {
+                    syn::NestedMeta::Meta(syn::Meta::Path(path))
+                        if path.is_ident("constructor") =>
+                    {
+                        options.constructor = true;
+                    }
+                    syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("no_std") => {
+                        options.no_std = true;
+                    }
+                    syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("traits") => {
+                        options.traits = true;
+                    }
+                    _ => {
+                        return Err(syn::Error::new_spanned(
+                            meta,
+                            "expected one of `builder(constructor)`, `builder(no_std)`, `builder(traits)`",
+                        ))
+                    }
+                }
+            }
+        } else {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "expected one of `builder(constructor)`, `builder(no_std)`, `builder(traits)`",
+            ));
+        }
+    }
+
+    Ok(options)
+}
+
+// 实现builder_taint中的Builder与Buildable，使调用方可以编写对所有可构建类型通用的代码
+// 两个trait的方法都直接转发给同名的固有方法
+fn generate_builder_taint_impls(
+    struct_ident: &syn::Ident,
+    builder_name_ident: &syn::Ident,
+    generics: &syn::Generics,
+    options: &BuilderOptions,
+) -> proc_macro2::TokenStream {
+    let result_path = options.result_path();
+    let error_ty = options.error_type(builder_name_ident);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    quote::quote! {
+        impl #impl_generics ::builder_taint::Builder for #builder_name_ident #ty_generics #where_clause {
+            type Target = #struct_ident #ty_generics;
+            type Error = #error_ty;
+
+            fn build(&mut self)->#result_path<Self::Target,Self::Error>{
+                #builder_name_ident::build(self)
+            }
+        }
+
+        impl #impl_generics ::builder_taint::Buildable for #struct_ident #ty_generics #where_clause {
+            type Builder = #builder_name_ident #ty_generics;
+
+            fn builder()-> Self::Builder {
+                <#struct_ident #ty_generics>::builder()
+            }
+        }
+    }
+}
+
+// 实现builder_taint::DynBuilder，按字段名把Box<dyn Any>中的值设置到对应字段上
+fn generate_dyn_builder_impl(
+    fields: &StructFields,
+    builder_name_ident: &syn::Ident,
+    generics: &syn::Generics,
+    options: &BuilderOptions,
+) -> syn::Result<proc_macro2::TokenStream> {
+    // Box<dyn Any>只能装'static的值，带生命周期参数的结构体无法实现DynBuilder
+    if generics.lifetimes().next().is_some() {
+        return Ok(proc_macro2::TokenStream::new());
+    }
+    let mut generics = generics.clone();
+    let type_param_idents: Vec<_> = generics.type_params().map(|t| t.ident.clone()).collect();
+    let where_clause = generics.make_where_clause();
+    for ident in type_param_idents {
+        where_clause
+            .predicates
+            .push(syn::parse_quote!(#ident: 'static));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let option_path = options.option_path();
+    let result_path = options.result_path();
+    let box_path = options.box_path();
+    let any_path = options.any_path();
+
+    let mut field_name_literals = Vec::new();
+    let mut value_types = Vec::new();
+    let mut assign_clauses = Vec::new();
+    for field in fields.iter() {
+        let ident = &field.ident;
+        field_name_literals.push(ident.as_ref().unwrap().to_string());
+
+        // 与setter保持一致：Option<T>字段接收T，each字段接收整个Vec
+        if let Some(inner_ty) = get_generic_inner_type(&field.ty, "Option") {
+            value_types.push(inner_ty);
+            assign_clauses.push(quote::quote!(self.#ident = #option_path::Some(*value);));
+        } else if get_user_specified_attribute_ident_for_vec(field)?.is_some() {
+            value_types.push(&field.ty);
+            assign_clauses.push(quote::quote!(self.#ident = *value;));
+        } else {
+            value_types.push(&field.ty);
+            assign_clauses.push(quote::quote!(self.#ident = #option_path::Some(*value);));
+        }
+    }
+
+    Ok(quote::quote! {
+        impl #impl_generics ::builder_taint::DynBuilder for #builder_name_ident #ty_generics #where_clause {
+            fn set_field(&mut self, name: &str, value: #box_path<dyn #any_path>)->#result_path<(), ::builder_taint::FieldError>{
+                match name {
+                    #(
+                        #field_name_literals => {
+                            let value = value.downcast::<#value_types>().map_err(|_| {
+                                ::builder_taint::FieldError::TypeMismatch {
+                                    field: #field_name_literals,
+                                    expected: ::core::any::type_name::<#value_types>(),
+                                }
+                            })?;
+                            #assign_clauses
+                        }
+                    )*
+                    _ => return #result_path::Err(::builder_taint::FieldError::unknown_field(name)),
+                }
+                #result_path::Ok(())
+            }
+
+            fn field_names(&self)->&'static [&'static str]{
+                &[#(#field_name_literals),*]
+            }
+
+            fn field_type_name(&self, name: &str)->#option_path<&'static str>{
+                match name {
+                    #(#field_name_literals => #option_path::Some(::core::any::type_name::<#value_types>()),)*
+                    _ => #option_path::None,
+                }
+            }
+        }
+    })
+}
+
 // 获取用户指定的惰性属性的值
 fn get_user_specified_attribute_ident_for_vec(
     field: &syn::Field,
@@ -260,38 +764,504 @@ fn do_expand(st: &syn::DeriveInput) -> syn::Result<TokenStream> {
     // 构建一个新的标志符
     let builder_name_ident = syn::Ident::new(&builder_name_literal, st.span());
 
+    // 获取结构体上#[builder(...)]中的选项
+    let options = get_struct_builder_options(st)?;
     // 获取派生中结构体Command struct fields
-    let fields = get_fields_from_derive_input(&st)?;
+    let fields = get_fields_from_derive_input(st)?;
     // 获取派生中结构体Command的属性字段
-    let builder_struct_fields_def = generate_builder_struct_fields_def(fields)?;
+    let builder_struct_fields_def = generate_builder_struct_fields_def(fields, &options)?;
     // 初始化CommandBuilder的新实例字段
-    let init_clauses = generate_builder_struct_factory_init_clauses(fields)?;
+    let init_clauses = generate_builder_struct_factory_init_clauses(fields, &options)?;
     // 生成setter方法
-    let setter_functions = generate_setter_functions(fields)?;
+    let setter_functions = generate_setter_functions(fields, &st.vis, &options)?;
 
     let struct_ident = &st.ident;
-    let build_function = generate_build_function(&fields, struct_ident)?;
-    /// quote::quote!{}
-    /// 可以将语法树节点及其子节点重新转化为proc_macro2::TokenStream
-    /// 但是可以通过into()转换为proc_macro::TokenStream
+    let build_function = generate_build_function(
+        fields,
+        struct_ident,
+        &builder_name_ident,
+        &st.generics,
+        &options,
+        false,
+    )?;
+    let build_and_reset_function = generate_build_function(
+        fields,
+        struct_ident,
+        &builder_name_ident,
+        &st.generics,
+        &options,
+        true,
+    )?;
+    let build_generics = generate_build_generics(fields, &st.generics);
+
+    let required_constructor = if options.constructor {
+        generate_required_constructor(fields, &builder_name_ident, &st.generics, &options)?
+    } else {
+        proc_macro2::TokenStream::new()
+    };
+    let mut builder_taint_impls = proc_macro2::TokenStream::new();
+    if options.traits {
+        builder_taint_impls.extend(generate_builder_taint_impls(
+            struct_ident,
+            &builder_name_ident,
+            &build_generics,
+            &options,
+        ));
+        builder_taint_impls.extend(generate_dyn_builder_impl(
+            fields,
+            &builder_name_ident,
+            &st.generics,
+            &options,
+        )?);
+    }
+    let builder_error_def = if options.no_std {
+        generate_builder_error_def(&builder_name_ident)
+    } else {
+        proc_macro2::TokenStream::new()
+    };
+
+    let builder_doc = format!(
+        "Builder for `{}`, created by `{}::builder()`.",
+        struct_ident, struct_ident
+    );
+    let builder_fn_doc = format!("Creates a `{}` with no fields set.", builder_name_ident);
+    // Builder带上与结构体相同的泛型参数，引用字段因此可以直接借用而不必拥有数据
+    let (impl_generics, ty_generics, where_clause) = st.generics.split_for_impl();
+    let (build_impl_generics, _, build_where_clause) = build_generics.split_for_impl();
+    // quote::quote!{}
+    // 可以将语法树节点及其子节点重新转化为proc_macro2::TokenStream
+    // 但是可以通过into()转换为proc_macro::TokenStream
     let expand = quote::quote! {
-        pub struct #builder_name_ident{
+        #[doc = #builder_doc]
+        #[must_use = "builders do nothing until `build()` is called"]
+        pub struct #builder_name_ident #impl_generics #where_clause {
             #builder_struct_fields_def
         }
 
-        impl #builder_name_ident {
+        impl #impl_generics #builder_name_ident #ty_generics #where_clause {
             #setter_functions
+            #build_and_reset_function
+        }
+
+        impl #build_impl_generics #builder_name_ident #ty_generics #build_where_clause {
             #build_function
         }
 
-        impl #struct_ident {
-            pub fn builder()-> #builder_name_ident {
+        #builder_error_def
+
+        #builder_taint_impls
+
+        impl #impl_generics #struct_ident #ty_generics #where_clause {
+            #[doc = #builder_fn_doc]
+            #[must_use = "builders do nothing until `build()` is called"]
+            pub fn builder()-> #builder_name_ident #ty_generics {
                  #builder_name_ident{
                     #(#init_clauses)*
                 }
             }
+
+            #required_constructor
         }
     };
 
     Ok(expand.into())
 }
+
+// 函数Builder中保存方法接收者的字段名
+const RECEIVER_FIELD: &str = "__receiver";
+// 函数Builder额外的生命周期参数，用于方法接收者以及参数类型中省略的生命周期
+const BUILDER_LIFETIME: &str = "'__builder";
+
+// 生成#[builder]函数对应的Builder
+// 返回值中第一部分是放在函数外面的Builder定义，第二部分是创建Builder的入口函数
+// self_ty 为Some时表示该函数是impl块中的方法或关联函数
+fn generate_fn_builder(
+    vis: &syn::Visibility,
+    sig: &mut syn::Signature,
+    self_ty: Option<&syn::Type>,
+) -> syn::Result<(proc_macro2::TokenStream, proc_macro2::TokenStream)> {
+    if let Some(param) = sig
+        .generics
+        .params
+        .iter()
+        .find(|param| !matches!(param, syn::GenericParam::Lifetime(_)))
+    {
+        return Err(syn::Error::new_spanned(
+            param,
+            "`#[builder]` does not support functions with type or const parameters",
+        ));
+    }
+    if let Some(ref asyncness) = sig.asyncness {
+        return Err(syn::Error::new_spanned(
+            asyncness,
+            "`#[builder]` does not support async functions",
+        ));
+    }
+
+    // 不在#[builder]的impl块中却用到了Self，说明是普通impl块中的方法或关联函数，
+    // 生成的Builder会被放进impl块里，只能在这里报错
+    if self_ty.is_none() {
+        let self_ident = syn::Ident::new("Self", proc_macro2::Span::call_site());
+        let arg_types = sig.inputs.iter().filter_map(|input| match input {
+            syn::FnArg::Typed(pat_type) => Some(&*pat_type.ty),
+            syn::FnArg::Receiver(_) => None,
+        });
+        let output_type = match sig.output {
+            syn::ReturnType::Type(_, ref ty) => Some(&**ty),
+            syn::ReturnType::Default => None,
+        };
+        let receiver = sig.inputs.iter().find_map(|input| match input {
+            syn::FnArg::Receiver(r) => Some(r),
+            syn::FnArg::Typed(_) => None,
+        });
+        let self_span = match receiver {
+            Some(r) => Some(quote::quote!(#r)),
+            None => arg_types
+                .chain(output_type)
+                .find(|ty| type_mentions_ident(ty, &self_ident))
+                .map(|ty| quote::quote!(#ty)),
+        };
+        if let Some(self_span) = self_span {
+            return Err(syn::Error::new_spanned(
+                self_span,
+                "methods must be inside an impl block marked with `#[builder]`",
+            ));
+        }
+    }
+
+    let builder_lifetime = syn::Lifetime::new(BUILDER_LIFETIME, sig.ident.span());
+    let mut uses_builder_lifetime = false;
+    let mut receiver = None;
+    let mut fields = StructFields::new();
+    for input in sig.inputs.iter_mut() {
+        match input {
+            syn::FnArg::Receiver(r) => {
+                if r.reference.is_none() {
+                    return Err(syn::Error::new_spanned(
+                        r,
+                        "`#[builder]` does not support methods taking `self` by value",
+                    ));
+                }
+                // 接收者上写明的生命周期要保留，返回值可能借用它，只有省略时才用BUILDER_LIFETIME
+                let lifetime = match r.reference {
+                    Some((_, Some(ref lifetime))) if lifetime.ident != "_" => lifetime.clone(),
+                    _ => {
+                        uses_builder_lifetime = true;
+                        builder_lifetime.clone()
+                    }
+                };
+                receiver = Some((r.mutability.is_some(), lifetime));
+            }
+            syn::FnArg::Typed(pat_type) => {
+                let ident = match *pat_type.pat {
+                    syn::Pat::Ident(ref pat_ident) => pat_ident.ident.clone(),
+                    ref pat => {
+                        return Err(syn::Error::new_spanned(
+                            pat,
+                            "`#[builder]` arguments must be plain identifiers",
+                        ))
+                    }
+                };
+
+                // 参数上的#[builder(...)]只给宏使用，需要从原函数上去掉
+                let (builder_attrs, other_attrs) = pat_type
+                    .attrs
+                    .drain(..)
+                    .partition(|attr| attr.path.is_ident("builder"));
+                pat_type.attrs = other_attrs;
+
+                let mut ty = (*pat_type.ty).clone();
+                if let Some(self_ty) = self_ty {
+                    ty = replace_self_type(&ty, self_ty)?;
+                }
+                uses_builder_lifetime |= fill_elided_lifetimes(&mut ty, &builder_lifetime);
+
+                fields.push(syn::Field {
+                    attrs: builder_attrs,
+                    vis: syn::Visibility::Inherited,
+                    ident: Some(ident),
+                    colon_token: Some(Default::default()),
+                    ty,
+                });
+            }
+        }
+    }
+
+    let fn_ident = &sig.ident;
+    let fn_name_literal = to_upper_camel_case(&fn_ident.to_string());
+    let builder_name_literal = match self_ty {
+        Some(self_ty) => format!("{}{}Builder", get_type_name(self_ty)?, fn_name_literal),
+        None => format!("{}Builder", fn_name_literal),
+    };
+    let builder_name_ident = syn::Ident::new(&builder_name_literal, fn_ident.span());
+    let entry_fn_ident = syn::Ident::new(&format!("{}_builder", fn_ident), fn_ident.span());
+
+    let options = BuilderOptions::default();
+    let builder_struct_fields_def = generate_builder_struct_fields_def(&fields, &options)?;
+    let init_clauses = generate_builder_struct_factory_init_clauses(&fields, &options)?;
+    let setter_functions = generate_setter_functions(&fields, vis, &options)?;
+    let (checker_code_pieces, value_exprs) =
+        generate_build_checks_and_values(&fields, &options, &builder_name_ident, true)?;
+
+    let output_ty = match sig.output {
+        syn::ReturnType::Default => quote::quote!(()),
+        syn::ReturnType::Type(_, ref ty) => match self_ty {
+            Some(self_ty) => {
+                let ty = replace_self_type(ty, self_ty)?;
+                quote::quote!(#ty)
+            }
+            None => quote::quote!(#ty),
+        },
+    };
+
+    // Builder带上函数的生命周期参数，需要时在最前面加上BUILDER_LIFETIME
+    let mut builder_generics = syn::Generics {
+        params: sig.generics.params.clone(),
+        where_clause: sig.generics.where_clause.clone(),
+        ..Default::default()
+    };
+    if uses_builder_lifetime {
+        builder_generics.params.insert(
+            0,
+            syn::GenericParam::Lifetime(syn::LifetimeDef::new(builder_lifetime.clone())),
+        );
+    }
+    let (impl_generics, ty_generics, where_clause) = builder_generics.split_for_impl();
+
+    let receiver_ident = syn::Ident::new(RECEIVER_FIELD, fn_ident.span());
+    let (receiver_field_def, receiver_init, callee) = match (self_ty, &receiver) {
+        (Some(self_ty), Some((is_mut, lifetime))) => {
+            let reference = if *is_mut {
+                quote::quote!(&#lifetime mut)
+            } else {
+                quote::quote!(&#lifetime)
+            };
+            (
+                quote::quote!(#receiver_ident : #reference #self_ty,),
+                quote::quote!(#receiver_ident : self,),
+                quote::quote!(self.#receiver_ident.#fn_ident),
+            )
+        }
+        (Some(self_ty), None) => (
+            quote::quote!(),
+            quote::quote!(),
+            quote::quote!(<#self_ty>::#fn_ident),
+        ),
+        (None, Some(_)) => unreachable!(),
+        (None, None) => (quote::quote!(), quote::quote!(), quote::quote!(#fn_ident)),
+    };
+    let entry_fn_receiver = match receiver {
+        Some((true, ref lifetime)) => quote::quote!(&#lifetime mut self),
+        Some((false, ref lifetime)) => quote::quote!(&#lifetime self),
+        None => quote::quote!(),
+    };
+
+    let fn_path_literal = match self_ty {
+        Some(self_ty) => format!("{}::{}", get_type_name(self_ty)?, fn_ident),
+        None => fn_ident.to_string(),
+    };
+    let builder_doc = format!(
+        "Builder for calling `{}` with named arguments.",
+        fn_path_literal
+    );
+    let call_doc = generate_required_fields_doc(
+        &fields,
+        &format!(
+            "Calls `{}` with the arguments set so far.\n\n\
+             The arguments are moved out of the builder, so they have to be set again \
+             before calling it another time.",
+            fn_path_literal
+        ),
+    )?;
+    let entry_fn_doc = format!("Creates a `{}` with no arguments set.", builder_name_ident);
+
+    let builder_def = quote::quote! {
+        #[doc = #builder_doc]
+        #[must_use = "builders do nothing until `call()` is called"]
+        #vis struct #builder_name_ident #impl_generics #where_clause {
+            #receiver_field_def
+            #builder_struct_fields_def
+        }
+
+        impl #impl_generics #builder_name_ident #ty_generics #where_clause {
+            #setter_functions
+
+            #[doc = #call_doc]
+            pub fn call(&mut self)->std::result::Result<#output_ty,std::boxed::Box<dyn std::error::Error>>{
+                #(#checker_code_pieces)*
+
+                std::result::Result::Ok(#callee(#(#value_exprs),*))
+            }
+        }
+    };
+
+    let entry_fn = quote::quote! {
+        #[doc = #entry_fn_doc]
+        #[must_use = "builders do nothing until `call()` is called"]
+        #vis fn #entry_fn_ident #impl_generics (#entry_fn_receiver) -> #builder_name_ident #ty_generics #where_clause {
+            #builder_name_ident {
+                #receiver_init
+                #(#init_clauses)*
+            }
+        }
+    };
+
+    Ok((builder_def, entry_fn))
+}
+
+// 自由函数：Builder与入口函数都放在函数旁边
+fn do_expand_fn(mut item_fn: syn::ItemFn) -> syn::Result<proc_macro2::TokenStream> {
+    let (builder_def, entry_fn) = generate_fn_builder(&item_fn.vis, &mut item_fn.sig, None)?;
+
+    Ok(quote::quote! {
+        #item_fn
+        #builder_def
+        #entry_fn
+    })
+}
+
+// impl块：处理其中标注了#[builder]的方法，入口函数放进impl块里，Builder放在impl块外面
+fn do_expand_impl(mut item_impl: syn::ItemImpl) -> syn::Result<proc_macro2::TokenStream> {
+    if !item_impl.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &item_impl.generics,
+            "`#[builder]` does not support generic impl blocks",
+        ));
+    }
+    if let Some((_, ref path, _)) = item_impl.trait_ {
+        return Err(syn::Error::new_spanned(
+            path,
+            "`#[builder]` cannot be applied to trait impls",
+        ));
+    }
+
+    let self_ty = (*item_impl.self_ty).clone();
+    let mut builder_defs = proc_macro2::TokenStream::new();
+    let mut entry_fns = Vec::new();
+
+    for item in item_impl.items.iter_mut() {
+        if let syn::ImplItem::Method(method) = item {
+            let attr_count = method.attrs.len();
+            method.attrs.retain(|attr| !attr.path.is_ident("builder"));
+            if method.attrs.len() == attr_count {
+                continue;
+            }
+
+            let (builder_def, entry_fn) =
+                generate_fn_builder(&method.vis, &mut method.sig, Some(&self_ty))?;
+            builder_defs.extend(builder_def);
+            entry_fns.push(syn::parse2::<syn::ImplItem>(entry_fn)?);
+        }
+    }
+    item_impl.items.extend(entry_fns);
+
+    Ok(quote::quote! {
+        #item_impl
+        #builder_defs
+    })
+}
+
+// 取impl块的类型名，用作Builder名字的前缀
+fn get_type_name(ty: &syn::Type) -> syn::Result<String> {
+    if let syn::Type::Path(syn::TypePath { ref path, .. }) = ty {
+        if let Some(seg) = path.segments.last() {
+            return Ok(seg.ident.to_string());
+        }
+    }
+    Err(syn::Error::new_spanned(
+        ty,
+        "`#[builder]` impl blocks must be for a named type",
+    ))
+}
+
+// Builder的impl块中Self指的是Builder自己，所以要把方法签名里的Self换成impl块的类型
+fn replace_self_type(ty: &syn::Type, self_ty: &syn::Type) -> syn::Result<syn::Type> {
+    fn replace(tokens: proc_macro2::TokenStream, self_ty: &syn::Type) -> proc_macro2::TokenStream {
+        tokens
+            .into_iter()
+            .map(|tt| match tt {
+                proc_macro2::TokenTree::Ident(ref ident) if ident == "Self" => {
+                    quote::quote!(#self_ty)
+                }
+                proc_macro2::TokenTree::Group(group) => {
+                    let mut new_group = proc_macro2::Group::new(
+                        group.delimiter(),
+                        replace(group.stream(), self_ty),
+                    );
+                    new_group.set_span(group.span());
+                    quote::quote!(#new_group)
+                }
+                other => quote::quote!(#other),
+            })
+            .collect()
+    }
+
+    syn::parse2(replace(quote::quote!(#ty), self_ty))
+}
+
+// 把参数类型中省略的生命周期（&T、'_）替换为lifetime，返回是否做了替换
+// fn指针类型有自己的省略规则，不进入其中
+fn fill_elided_lifetimes(ty: &mut syn::Type, lifetime: &syn::Lifetime) -> bool {
+    match ty {
+        syn::Type::Reference(reference) => {
+            let mut filled = false;
+            match reference.lifetime {
+                None => {
+                    reference.lifetime = Some(lifetime.clone());
+                    filled = true;
+                }
+                Some(ref mut l) if l.ident == "_" => {
+                    *l = lifetime.clone();
+                    filled = true;
+                }
+                Some(_) => {}
+            }
+            fill_elided_lifetimes(&mut reference.elem, lifetime) | filled
+        }
+        syn::Type::Path(type_path) => {
+            let mut filled = false;
+            if let Some(ref mut qself) = type_path.qself {
+                filled |= fill_elided_lifetimes(&mut qself.ty, lifetime);
+            }
+            for seg in type_path.path.segments.iter_mut() {
+                if let syn::PathArguments::AngleBracketed(ref mut args) = seg.arguments {
+                    for arg in args.args.iter_mut() {
+                        match arg {
+                            syn::GenericArgument::Lifetime(l) if l.ident == "_" => {
+                                *l = lifetime.clone();
+                                filled = true;
+                            }
+                            syn::GenericArgument::Type(ty) => {
+                                filled |= fill_elided_lifetimes(ty, lifetime);
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            filled
+        }
+        syn::Type::Slice(slice) => fill_elided_lifetimes(&mut slice.elem, lifetime),
+        syn::Type::Array(array) => fill_elided_lifetimes(&mut array.elem, lifetime),
+        syn::Type::Ptr(ptr) => fill_elided_lifetimes(&mut ptr.elem, lifetime),
+        syn::Type::Paren(paren) => fill_elided_lifetimes(&mut paren.elem, lifetime),
+        syn::Type::Group(group) => fill_elided_lifetimes(&mut group.elem, lifetime),
+        syn::Type::Tuple(tuple) => tuple.elems.iter_mut().fold(false, |filled, elem| {
+            fill_elided_lifetimes(elem, lifetime) | filled
+        }),
+        _ => false,
+    }
+}
+
+// spawn_process => SpawnProcess
+fn to_upper_camel_case(s: &str) -> String {
+    s.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,101 @@
+// Functions with many parameters are just as easy to call with the arguments in
+// the wrong order as structs with many fields are to construct incorrectly.
+//
+// Place #[builder] on a free function to generate a builder named after the
+// function, whose call() method invokes the function with the collected
+// arguments. Arguments are classified exactly like struct fields: Option
+// arguments are optional, #[builder(each = "...")] arguments are repeated, and
+// everything else is required.
+//
+// Methods and associated functions are supported by marking both the impl block
+// and the method with #[builder]. The entry point is then Type::method_builder()
+// or value.method_builder(), and the builder is prefixed with the type name.
+
+use derive_builder::builder;
+
+#[builder]
+pub fn spawn(
+    exe: String,
+    #[builder(each = "arg")] args: Vec<String>,
+    cwd: Option<String>,
+) -> String {
+    let mut line = exe;
+    for arg in args {
+        line.push(' ');
+        line.push_str(&arg);
+    }
+    if let Some(cwd) = cwd {
+        line = format!("cd {} && {}", cwd, line);
+    }
+    line
+}
+
+pub struct Runner {
+    prefix: String,
+    launched: usize,
+}
+
+#[builder]
+impl Runner {
+    #[builder]
+    pub fn new(prefix: String, launched: Option<usize>) -> Self {
+        Runner {
+            prefix,
+            launched: launched.unwrap_or(0),
+        }
+    }
+
+    #[builder]
+    pub fn describe(&self, exe: String, verbose: Option<bool>) -> String {
+        format!("{} {} {}", self.prefix, exe, verbose.unwrap_or(false))
+    }
+
+    #[builder]
+    pub fn launch(&mut self, exe: String) -> usize {
+        let _ = exe;
+        self.launched += 1;
+        self.launched
+    }
+}
+
+fn main() {
+    let line = spawn_builder()
+        .exe("cargo".to_owned())
+        .arg("build".to_owned())
+        .arg("--release".to_owned())
+        .call()
+        .unwrap();
+    assert_eq!(line, "cargo build --release");
+
+    let line = spawn_builder()
+        .exe("ls".to_owned())
+        .cwd("/tmp".to_owned())
+        .call()
+        .unwrap();
+    assert_eq!(line, "cd /tmp && ls");
+
+    assert!(spawn_builder().arg("build".to_owned()).call().is_err());
+
+    let mut runner = Runner::new_builder()
+        .prefix("sudo".to_owned())
+        .call()
+        .unwrap();
+    assert_eq!(runner.launched, 0);
+
+    let description = runner
+        .describe_builder()
+        .exe("cargo".to_owned())
+        .verbose(true)
+        .call()
+        .unwrap();
+    assert_eq!(description, "sudo cargo true");
+
+    assert_eq!(
+        runner.launch_builder().exe("a".to_owned()).call().unwrap(),
+        1
+    );
+    assert_eq!(
+        runner.launch_builder().exe("b".to_owned()).call().unwrap(),
+        2
+    );
+}
@@ -0,0 +1,42 @@
+// Most call sites only ever set the required fields. Opting in with a
+// struct-level #[builder(constructor)] attribute generates a positional
+// constructor taking exactly the required fields in declaration order, that is
+// every field which is neither an Option nor marked with
+// #[builder(each = "...")].
+//
+//     impl Command {
+//         pub fn new_required(executable: String, args: Vec<String>) -> CommandBuilder {...}
+//     }
+//
+// The returned builder leaves all optional fields unset so that they can still
+// be tweaked before calling build().
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(constructor)]
+pub struct Command {
+    executable: String,
+    args: Vec<String>,
+    #[builder(each = "env")]
+    env: Vec<String>,
+    current_dir: Option<String>,
+}
+
+fn main() {
+    let command = Command::new_required("cargo".to_owned(), vec!["build".to_owned()])
+        .build()
+        .unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build"]);
+    assert!(command.env.is_empty());
+    assert!(command.current_dir.is_none());
+
+    let command = Command::new_required("cargo".to_owned(), vec![])
+        .env("RUST_LOG=debug".to_owned())
+        .current_dir("..".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(command.env, vec!["RUST_LOG=debug"]);
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+}
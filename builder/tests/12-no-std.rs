@@ -0,0 +1,51 @@
+// Crates without the standard library can still use the builder as long as
+// they have an allocator. With #[builder(no_std)] on the struct, the generated
+// code refers only to ::core and ::alloc paths, so the caller needs
+// `extern crate alloc;` at its crate root.
+//
+// There is no std::error::Error in core, so build() instead returns a generated
+// CommandBuilderError which implements core::fmt::Display and names the missing
+// field.
+//
+// This test links std only to provide a panic handler for the binary; the
+// `std` name itself is not in scope.
+
+#![no_std]
+
+extern crate alloc;
+extern crate std as _;
+
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(no_std, constructor)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".into())
+        .arg("build".into())
+        .build()
+        .unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build"]);
+    assert!(command.current_dir.is_none());
+
+    let command = Command::new_required("cargo".into()).build().unwrap();
+    assert!(command.args.is_empty());
+
+    let err = match Command::builder().arg("build".into()).build() {
+        Ok(_) => panic!("executable is required"),
+        Err(err) => err,
+    };
+    assert_eq!(err.missing_field, "executable");
+    assert_eq!(err.to_string(), "executable field missing");
+}
@@ -0,0 +1,55 @@
+// Code that is generic over many configuration types needs a way to talk about
+// "a type with a builder" without naming each generated builder.
+//
+// The builder_taint crate defines two traits for this. With #[builder(traits)]
+// on the struct, the derive implements both of them:
+//
+//     impl builder_taint::Builder for CommandBuilder {
+//         type Target = Command;
+//         type Error = Box<dyn Error>;
+//         fn build(&mut self) -> Result<Command, Self::Error> {...}
+//     }
+//
+//     impl builder_taint::Buildable for Command {
+//         type Builder = CommandBuilder;
+//         fn builder() -> CommandBuilder {...}
+//     }
+
+use builder_taint::{Buildable, Builder as _};
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(traits)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+}
+
+#[derive(Builder)]
+#[builder(traits)]
+pub struct Job {
+    name: Option<String>,
+    retries: Option<u32>,
+}
+
+fn load_default<T: Buildable>() -> Result<T, <T::Builder as builder_taint::Builder>::Error> {
+    T::builder().build()
+}
+
+fn main() {
+    let job: Job = load_default().unwrap();
+    assert!(job.name.is_none());
+    assert!(job.retries.is_none());
+
+    let err = load_default::<Command>().err().unwrap();
+    assert_eq!(err.to_string(), "executable field missing");
+
+    let mut builder = <Command as Buildable>::builder();
+    builder
+        .executable("cargo".to_owned())
+        .arg("build".to_owned());
+    let command = builder_taint::Builder::build(&mut builder).unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build"]);
+}
@@ -0,0 +1,73 @@
+// Configuration that only arrives at runtime as key/value pairs can be applied
+// to any derived builder through the builder_taint::DynBuilder trait, which
+// the derive implements alongside the regular setters when the struct is
+// marked #[builder(traits)].
+//
+// set_field takes the field name and a Box<dyn Any> holding the value. The
+// value must have the same type the ordinary setter accepts: Option<T> fields
+// take a T, and #[builder(each = "...")] fields take the whole Vec<T>.
+// field_names and field_type_name describe what the builder accepts so that a
+// host can validate configuration before applying it.
+
+use builder_taint::{DynBuilder, FieldError};
+use derive_builder::Builder;
+use std::any::Any;
+
+#[derive(Builder)]
+#[builder(traits)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+}
+
+fn apply(
+    builder: &mut dyn DynBuilder,
+    config: Vec<(&str, Box<dyn Any>)>,
+) -> Result<(), FieldError> {
+    for (name, value) in config {
+        builder.set_field(name, value)?;
+    }
+    Ok(())
+}
+
+fn main() {
+    let mut builder = Command::builder();
+    assert_eq!(
+        builder.field_names(),
+        &["executable", "args", "current_dir"]
+    );
+    assert_eq!(
+        builder.field_type_name("current_dir"),
+        Some(std::any::type_name::<String>())
+    );
+    assert_eq!(builder.field_type_name("missing"), None);
+
+    apply(
+        &mut builder,
+        vec![
+            ("executable", Box::new("cargo".to_owned())),
+            ("args", Box::new(vec!["build".to_owned()])),
+            ("current_dir", Box::new("..".to_owned())),
+        ],
+    )
+    .unwrap();
+    let command = builder.build().unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build"]);
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+
+    let mut builder = Command::builder();
+    assert_eq!(
+        builder.set_field("executable", Box::new(1u8)),
+        Err(FieldError::TypeMismatch {
+            field: "executable",
+            expected: std::any::type_name::<String>(),
+        })
+    );
+    assert_eq!(
+        builder.set_field("cwd", Box::new("..".to_owned())),
+        Err(FieldError::UnknownField("cwd".to_owned()))
+    );
+}
@@ -0,0 +1,34 @@
+//! The generated builder is part of the caller's public API, so it should show
+//! up in rustdoc as well documented as the struct it builds.
+//!
+//! Each setter carries the `///` doc comments of its field, falling back to a
+//! short generated sentence for undocumented fields. build() gets a doc block
+//! listing the required fields, and every other public item is documented too,
+//! so the derive can be used in crates that deny missing_docs.
+
+#![deny(missing_docs)]
+
+use derive_builder::Builder;
+
+/// A process to spawn.
+#[derive(Builder)]
+#[builder(constructor)]
+pub struct Command {
+    /// Path of the program to run.
+    executable: String,
+    /// Arguments passed to the program.
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+}
+
+fn main() {
+    let command = Command::new_required("cargo".to_owned())
+        .arg("build".to_owned())
+        .current_dir("..".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build"]);
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+}
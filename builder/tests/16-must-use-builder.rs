@@ -0,0 +1,16 @@
+// A builder on its own does nothing until build() is called, so creating one
+// and dropping it is almost certainly a mistake. Both the builder struct and
+// the builder() constructor are marked #[must_use].
+
+#![deny(unused_must_use)]
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+}
+
+fn main() {
+    Command::builder();
+}
@@ -0,0 +1,93 @@
+// Structs that borrow their data need a builder that borrows too. The builder
+// carries the same generic parameters as the struct, so for Query<'a> the
+// derive generates QueryBuilder<'a> whose setters take &'a str directly.
+//
+// Shared reference fields are copied out of the builder by build() instead of
+// being cloned, so the referenced data does not need to implement Clone and is
+// never copied.
+//
+// Other fields, including a `Vec<&'a str>`, are cloned by build(), because
+// build() leaves the builder untouched so it can be reused. build() is
+// therefore only available when the type parameters used in those fields
+// implement Clone. build_and_reset() instead moves every value out of the
+// builder, leaving it empty: nothing is cloned or allocated, and it has no
+// Clone requirement.
+
+use derive_builder::Builder;
+
+// Deliberately not Clone.
+pub struct Schema {
+    name: String,
+}
+
+#[derive(Builder)]
+#[builder(constructor)]
+pub struct Query<'a> {
+    table: &'a str,
+    schema: &'a Schema,
+    #[builder(each = "filter")]
+    filters: Vec<&'a str>,
+    alias: Option<&'a str>,
+}
+
+#[derive(Builder)]
+pub struct Pair<T> {
+    left: T,
+    right: Option<T>,
+}
+
+#[derive(Builder)]
+pub struct Borrowed<'a, T> {
+    value: &'a T,
+}
+
+fn main() {
+    let schema = Schema {
+        name: "public".to_owned(),
+    };
+    let table = String::from("users");
+
+    let query = Query::builder()
+        .table(&table)
+        .schema(&schema)
+        .filter("age > 18")
+        .filter("active")
+        .build()
+        .unwrap();
+    assert_eq!(query.table, "users");
+    assert!(std::ptr::eq(query.table, table.as_str()));
+    assert_eq!(query.schema.name, "public");
+    assert_eq!(query.filters, vec!["age > 18", "active"]);
+    assert!(query.alias.is_none());
+
+    let query = Query::new_required(&table, &schema)
+        .alias("u")
+        .build()
+        .unwrap();
+    assert_eq!(query.alias, Some("u"));
+
+    let pair = Pair::builder().left(1u8).right(2).build().unwrap();
+    assert_eq!(pair.left, 1);
+    assert_eq!(pair.right, Some(2));
+
+    let pair = Pair::builder()
+        .left(Schema {
+            name: "left".to_owned(),
+        })
+        .build_and_reset()
+        .unwrap();
+    assert_eq!(pair.left.name, "left");
+    assert!(pair.right.is_none());
+
+    let mut builder = Query::builder();
+    builder.table(&table).schema(&schema).filter("active");
+    let query = builder.build_and_reset().unwrap();
+    assert_eq!(query.filters, vec!["active"]);
+    // Everything was moved out, so the builder starts over.
+    assert!(builder.build().is_err());
+    let query = builder.table(&table).schema(&schema).build().unwrap();
+    assert!(query.filters.is_empty());
+
+    let borrowed = Borrowed::builder().value(&schema).build().unwrap();
+    assert!(std::ptr::eq(borrowed.value, &schema));
+}
@@ -0,0 +1,52 @@
+// The setters of a function builder have the same visibility as the function
+// itself, so a public #[builder] function declared inside a module can be
+// called through its builder from outside that module.
+
+mod process {
+    use derive_builder::builder;
+
+    #[builder]
+    pub fn spawn(exe: String, #[builder(each = "arg")] args: Vec<String>) -> String {
+        let mut line = exe;
+        for arg in args {
+            line.push(' ');
+            line.push_str(&arg);
+        }
+        line
+    }
+
+    pub struct Shell {
+        pub name: String,
+    }
+
+    #[builder]
+    impl Shell {
+        #[builder]
+        pub fn run(&self, command: String, login: Option<bool>) -> String {
+            let flag = if login.unwrap_or(false) { " -l" } else { "" };
+            format!("{}{} -c '{}'", self.name, flag, command)
+        }
+    }
+}
+
+use process::{spawn_builder, Shell};
+
+fn main() {
+    let line = spawn_builder()
+        .exe("cargo".to_owned())
+        .arg("test".to_owned())
+        .call()
+        .unwrap();
+    assert_eq!(line, "cargo test");
+
+    let shell = Shell {
+        name: "bash".to_owned(),
+    };
+    let line = shell
+        .run_builder()
+        .command("ls".to_owned())
+        .login(true)
+        .call()
+        .unwrap();
+    assert_eq!(line, "bash -l -c 'ls'");
+}
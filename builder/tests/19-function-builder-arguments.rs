@@ -0,0 +1,116 @@
+// Arguments are moved out of a function builder when call() runs, so they do
+// not need to implement Clone and large values are never copied. The builder
+// is left empty afterwards and has to be filled in again for another call.
+//
+// Borrowed arguments work too: the builder carries the function's lifetime
+// parameters, and elided lifetimes in argument types such as `&str` or
+// `Option<&Path>` are tied to the builder itself. A lifetime written on `self`
+// is kept, so a method can return data borrowed from its receiver.
+
+use derive_builder::builder;
+use std::path::Path;
+
+pub struct Job {
+    pub name: String,
+}
+
+#[builder]
+pub fn run(
+    job: Job,
+    exe: &str,
+    cwd: Option<&Path>,
+    #[builder(each = "arg")] args: Vec<&str>,
+) -> String {
+    let mut line = format!("[{}] {}", job.name, exe);
+    for arg in args {
+        line.push(' ');
+        line.push_str(arg);
+    }
+    if let Some(cwd) = cwd {
+        line = format!("cd {} && {}", cwd.display(), line);
+    }
+    line
+}
+
+#[builder]
+pub fn longest<'a>(first: &'a str, second: &'a str) -> &'a str {
+    if second.len() > first.len() {
+        second
+    } else {
+        first
+    }
+}
+
+pub struct Queue {
+    jobs: Vec<Job>,
+}
+
+#[builder]
+impl Queue {
+    #[builder]
+    pub fn push(&mut self, job: Job, prefix: Option<&str>) -> usize {
+        let name = format!("{}{}", prefix.unwrap_or(""), job.name);
+        self.jobs.push(Job { name });
+        self.jobs.len()
+    }
+
+    #[builder]
+    pub fn find<'a>(&'a self, name: &'a str) -> Option<&'a Job> {
+        self.jobs.iter().find(|job| job.name == name)
+    }
+}
+
+fn main() {
+    let exe = String::from("cargo");
+    let dir = Path::new("/tmp");
+
+    let mut builder = run_builder();
+    builder
+        .job(Job {
+            name: "build".to_owned(),
+        })
+        .exe(&exe)
+        .cwd(dir)
+        .arg("build")
+        .arg("--release");
+    assert_eq!(
+        builder.call().unwrap(),
+        "cd /tmp && [build] cargo build --release"
+    );
+
+    // Everything was moved into the first call.
+    assert!(builder.call().is_err());
+
+    let line = builder
+        .job(Job {
+            name: "test".to_owned(),
+        })
+        .exe("cargo")
+        .call()
+        .unwrap();
+    assert_eq!(line, "[test] cargo");
+
+    let a = String::from("short");
+    let b = String::from("much longer");
+    assert_eq!(
+        longest_builder().first(&a).second(&b).call().unwrap(),
+        "much longer"
+    );
+
+    let mut queue = Queue { jobs: Vec::new() };
+    let prefix = String::from("nightly-");
+    let len = queue
+        .push_builder()
+        .job(Job {
+            name: "docs".to_owned(),
+        })
+        .prefix(&prefix)
+        .call()
+        .unwrap();
+    assert_eq!(len, 1);
+    assert_eq!(queue.jobs[0].name, "nightly-docs");
+
+    let name = String::from("nightly-docs");
+    let found = queue.find_builder().name(&name).call().unwrap();
+    assert!(std::ptr::eq(found.unwrap(), &queue.jobs[0]));
+}
@@ -0,0 +1,31 @@
+// #[builder] on a method or associated function only works when the impl block
+// itself is also marked with #[builder], because the generated builder struct
+// has to be placed outside the impl block. When the signature shows that the
+// function belongs to an impl block, through a `self` receiver or a mention of
+// `Self`, this is reported with one clear error instead of the errors about
+// the struct that would end up inside the impl block.
+
+use derive_builder::builder;
+
+pub struct Counter {
+    count: u32,
+}
+
+impl Counter {
+    #[builder]
+    pub fn make(count: u32) -> Self {
+        Counter { count }
+    }
+
+    #[builder]
+    pub fn merge(left: Self, right: Self) -> u32 {
+        left.count + right.count
+    }
+
+    #[builder]
+    pub fn add(&mut self, amount: u32) {
+        self.count += amount;
+    }
+}
+
+fn main() {}
@@ -10,4 +10,15 @@ fn tests() {
     t.pass("tests/07-repeated-field.rs");
     t.compile_fail("tests/08-unrecognized-attribute.rs");
     t.pass("tests/09-redefined-prelude-types.rs");
+    t.pass("tests/10-function-builder.rs");
+    t.pass("tests/11-required-constructor.rs");
+    t.pass("tests/12-no-std.rs");
+    t.pass("tests/13-builder-traits.rs");
+    t.pass("tests/14-dyn-builder.rs");
+    t.pass("tests/15-documented-api.rs");
+    t.compile_fail("tests/16-must-use-builder.rs");
+    t.pass("tests/17-borrowing-builder.rs");
+    t.pass("tests/18-function-builder-visibility.rs");
+    t.pass("tests/19-function-builder-arguments.rs");
+    t.compile_fail("tests/20-builder-outside-impl.rs");
 }
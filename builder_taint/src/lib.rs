@@ -1,3 +1,80 @@
+//! 结构体标注`#[builder(traits)]`时，`#[derive(Builder)]`生成的代码会为目标结构体实现`Buildable`，
+//! 为生成的Builder实现`Builder`，这样就可以编写对所有可构建类型通用的代码，
+//! 例如`fn load<T: Buildable>(cfg: &Path) -> T`。
+//! 派生宏同时实现了`DynBuilder`，可以在运行时按字段名设置字段。
+//! 使用`#[builder(traits)]`的crate需要依赖本crate。
+#![no_std]
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use core::any::Any;
+use core::fmt;
+
+/// 由`#[derive(Builder)]`生成的Builder实现
+pub trait Builder {
+    /// 构建出的目标类型
+    type Target;
+    /// 缺少必填字段时返回的错误
+    type Error;
+
+    fn build(&mut self) -> Result<Self::Target, Self::Error>;
+}
+
+/// 派生了`Builder`的目标类型
+pub trait Buildable: Sized {
+    type Builder: Builder<Target = Self>;
+
+    fn builder() -> Self::Builder;
+}
+
+/// 按字段名设置字段的Builder，用于在运行时把键值对配置映射到不同的Builder上
+pub trait DynBuilder {
+    /// 设置名为`name`的字段，`value`的类型必须与`field_type_name(name)`一致。
+    /// `Option<T>`字段接收`T`，`#[builder(each = "...")]`字段接收整个`Vec<T>`
+    fn set_field(&mut self, name: &str, value: Box<dyn Any>) -> Result<(), FieldError>;
+
+    /// 所有字段名，顺序与结构体中的定义一致
+    fn field_names(&self) -> &'static [&'static str];
+
+    /// `set_field`对该字段期望的值类型，字段不存在时返回`None`
+    fn field_type_name(&self, name: &str) -> Option<&'static str>;
+}
+
+/// `DynBuilder::set_field`的错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldError {
+    /// 没有这个字段
+    UnknownField(String),
+    /// 值的类型与字段期望的类型不一致
+    TypeMismatch {
+        field: &'static str,
+        expected: &'static str,
+    },
+}
+
+impl FieldError {
+    pub fn unknown_field(name: &str) -> Self {
+        FieldError::UnknownField(name.into())
+    }
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FieldError::UnknownField(name) => write!(f, "unknown field `{}`", name),
+            FieldError::TypeMismatch { field, expected } => {
+                write!(
+                    f,
+                    "field `{}` expects a value of type `{}`",
+                    field, expected
+                )
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -5,5 +82,3 @@ mod tests {
         assert_eq!(2 + 2, 4);
     }
 }
-
-pub trait Builder{}
\ No newline at end of file
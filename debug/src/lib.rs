@@ -1,11 +1,8 @@
 use proc_macro::TokenStream;
-use std::collections::HashMap;
 use syn::punctuated::Punctuated;
 use syn::visit::{self, Visit};
-use syn::Field;
-use syn::Token;
+use syn::{Field, Token};
 
-type StructFields = Punctuated<Field, Token![,]>;
 #[proc_macro_derive(CustomDebug, attributes(debug))]
 pub fn derive(input: TokenStream) -> TokenStream {
     let st = syn::parse_macro_input!(input as syn::DeriveInput);
@@ -21,97 +18,532 @@ fn do_expand(st: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
     Ok(ret)
 }
 
+#[proc_macro_derive(CustomLog, attributes(debug))]
+pub fn derive_log(input: TokenStream) -> TokenStream {
+    let st = syn::parse_macro_input!(input as syn::DeriveInput);
+
+    match do_expand_log(&st) {
+        Ok(tokenstream) => tokenstream.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn do_expand_log(st: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let ret = generate_log_methods(st)?;
+    Ok(ret)
+}
+
+#[proc_macro_derive(CustomDisplay, attributes(display))]
+pub fn derive_display(input: TokenStream) -> TokenStream {
+    let st = syn::parse_macro_input!(input as syn::DeriveInput);
+
+    match do_expand_display(&st) {
+        Ok(tokenstream) => tokenstream.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn do_expand_display(st: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let ret = generate_display_trait(st)?;
+    Ok(ret)
+}
+
 fn generate_debug_trait(st: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
-    let associated_types_map = get_generic_associated_types(st);
     let struct_name_ident = &st.ident;
 
     let mut generics = st.generics.clone();
+    let struct_options = get_struct_options(st)?;
 
-    let struct_name_literal = struct_name_ident.to_string();
+    // 联合体无法知道哪个字段有效，和#[debug(opaque)]一样只输出类型名和大小
+    if let (Some(_), _) | (None, syn::Data::Union(_)) = (struct_options.opaque, &st.data) {
+        return generate_opaque_debug(st, struct_options);
+    }
 
-    let mut fmt_body_stream = proc_macro2::TokenStream::new();
+    let printed = get_printed_fields(
+        st,
+        struct_options.fmt.as_ref(),
+        &struct_options.entries,
+        struct_options.reflect,
+    )?;
+    let mut helper_defs = generate_helper_defs(&printed);
 
-    fmt_body_stream.extend(quote::quote! {
-       fmt.debug_struct(#struct_name_literal)
-    });
-    let mut field_type_names = Vec::new();
-    let mut phantomdadta_type_param_names = Vec::new();
-    let fields = get_fields_from_derive_input(&st)?;
-    for field in fields.iter() {
-        if let Some(s) = get_field_type_name(field)? {
-            field_type_names.push(s);
+    let fmt_body_stream = match st.data {
+        syn::Data::Struct(syn::DataStruct { ref fields, .. }) => {
+            let struct_name_literal = match struct_options.name {
+                Some(ref name) => name.value(),
+                None => struct_name_ident.to_string(),
+            };
+            let (pattern, debug_stream) = match (&struct_options.transparent, &struct_options.fmt) {
+                (Some(transparent), Some(_)) => {
+                    return Err(syn::Error::new_spanned(
+                        transparent,
+                        "`debug(transparent)` cannot be combined with `debug(fmt = \"...\")`",
+                    ))
+                }
+                (None, Some(fmt_lit)) => {
+                    // 整个输出由格式化字符串决定，不再有结构体名和省略的字段
+                    if struct_options.name.is_some()
+                        || struct_options.non_exhaustive != NonExhaustive::Never
+                        || !struct_options.entries.is_empty()
+                    {
+                        return Err(syn::Error::new_spanned(
+                            fmt_lit,
+                            "`debug(fmt = \"...\")` cannot be combined with `debug(name = \"...\")`, `debug(non_exhaustive)` or `debug(entry(...))`",
+                        ));
+                    }
+                    generate_format_template(fields, fmt_lit)?
+                }
+                (Some(transparent), None) => {
+                    // 透明输出时没有结构体名，也没有可以省略的字段
+                    if struct_options.name.is_some()
+                        || struct_options.non_exhaustive != NonExhaustive::Never
+                        || !struct_options.entries.is_empty()
+                    {
+                        return Err(syn::Error::new_spanned(
+                            transparent,
+                            "`debug(transparent)` cannot be combined with `debug(name = \"...\")`, `debug(non_exhaustive)` or `debug(entry(...))`",
+                        ));
+                    }
+                    generate_transparent_debug(fields, transparent)?
+                }
+                (None, None) => generate_fields_debug(
+                    &struct_name_literal,
+                    fields,
+                    struct_options.non_exhaustive,
+                    &struct_options.entries,
+                )?,
+            };
+            quote::quote! {
+                match self {
+                    #struct_name_ident #pattern => #debug_stream,
+                }
+            }
         }
-        if let Some(s) = get_phantomdata_generic_type_name(field)? {
-            phantomdadta_type_param_names.push(s);
+        syn::Data::Enum(syn::DataEnum { ref variants, .. }) => {
+            if let Some(ref name) = struct_options.name {
+                return Err(syn::Error::new_spanned(
+                    name,
+                    "`debug(name = \"...\")` is only supported on structs",
+                ));
+            }
+            if let Some(ref transparent) = struct_options.transparent {
+                return Err(syn::Error::new_spanned(
+                    transparent,
+                    "`debug(transparent)` is only supported on structs",
+                ));
+            }
+            if let Some(ref fmt_lit) = struct_options.fmt {
+                return Err(syn::Error::new_spanned(
+                    fmt_lit,
+                    "`debug(fmt = \"...\")` is only supported on structs",
+                ));
+            }
+            if let Some(entry) = struct_options.entries.first() {
+                return Err(syn::Error::new_spanned(
+                    &entry.name,
+                    "`debug(entry(...))` is only supported on structs",
+                ));
+            }
+            let mut arms = Vec::new();
+            for variant in variants.iter() {
+                let variant_ident = &variant.ident;
+                let variant_name_literal = match get_variant_name(variant)? {
+                    Some(name) => name.value(),
+                    None => variant_ident.to_string(),
+                };
+                let (pattern, debug_stream) = generate_fields_debug(
+                    &variant_name_literal,
+                    &variant.fields,
+                    struct_options.non_exhaustive,
+                    &[],
+                )?;
+                arms.push(quote::quote! {
+                    Self::#variant_ident #pattern => #debug_stream,
+                });
+            }
+            if arms.is_empty() {
+                // 没有任何变体的枚举不可能有值，解引用后匹配空分支即可
+                quote::quote!(match *self {})
+            } else {
+                quote::quote! {
+                    match self {
+                        #(#arms)*
+                    }
+                }
+            }
         }
+        syn::Data::Union(_) => unreachable!(),
+    };
 
-        let field_name_ident = field.ident.as_ref().unwrap();
-
-        let field_name_literal = field_name_ident.to_string();
-        let mut format_str = "{:?}".to_string();
+    add_debug_bounds(st, &mut generics, struct_options.bound, printed);
 
-        if let Some(format) = get_custom_format_of_field(field)? {
-            format_str = format;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    // 整个输出由格式化字符串决定时，字段上的输出方式都用不到
+    let fmt_helper_defs = if struct_options.fmt.is_none() {
+        helper_defs.clone()
+    } else {
+        proc_macro2::TokenStream::new()
+    };
+    let depth_guard = match struct_options.max_depth {
+        Some(max_depth) => generate_depth_guard(max_depth),
+        None => proc_macro2::TokenStream::new(),
+    };
+    let mut ret_stream = quote::quote! {
+        impl #impl_generics ::core::fmt::Debug for #struct_name_ident #ty_generics #where_clause{
+            fn fmt(&self,fmt:&mut ::core::fmt::Formatter)->::core::fmt::Result{
+                #depth_guard
+                #fmt_helper_defs
+                #fmt_body_stream
+            }
         }
+    };
 
-        fmt_body_stream.extend(quote::quote! {
-            .field(#field_name_literal, &format_args!(#format_str,&self.#field_name_ident))
+    if struct_options.reflect {
+        let vis = &st.vis;
+        let fields_body_stream =
+            generate_debug_fields_body(st, &struct_options.entries, &mut helper_defs)?;
+        ret_stream.extend(quote::quote! {
+            impl #impl_generics #struct_name_ident #ty_generics #where_clause {
+                /// Returns each printed field's name and value, in the same form as the `Debug` output.
+                #vis fn debug_fields(
+                    &self,
+                ) -> impl ::core::iter::Iterator<Item = (&'static str, ::std::boxed::Box<dyn ::core::fmt::Debug + '_>)> + '_ {
+                    #helper_defs
+                    #fields_body_stream
+                }
+            }
         });
     }
 
-    fmt_body_stream.extend(quote::quote! {
-        .finish()
-    });
+    Ok(ret_stream)
+}
+
+// 参与输出的字段，以及推断约束、定义包装类型时需要的信息
+struct PrintedFields<'a> {
+    fields: Vec<&'a Field>,
+    // #[debug(limit = N)]的字段逐个输出其中的元素，约束加在泛型参数上
+    limited_fields: Vec<&'a Field>,
+    // 格式化字符串中用Debug以外的trait输出的字段，如{lo}需要Display
+    formatted_fields: Vec<(&'static str, &'a Field)>,
+    // 字段上#[debug(bound = "...")]指定的约束
+    bounds: Vec<syn::WherePredicate>,
+    uses_debug_with: bool,
+    uses_debug_limit: bool,
+    uses_debug_bytes: bool,
+    uses_debug_ptr: bool,
+}
+
+// 被#[debug(skip)]跳过、被#[debug(redact)]隐藏或者由#[debug(with = "...")]输出的字段
+// 不会调用Debug，也不参与泛型约束的推断
+// 带有#[debug(bound = "...")]的字段用指定的约束代替推断出的约束
+// 格式化字符串中引用到的字段，即使本身被跳过也会输出
+// struct_fmt存在时只输出其中引用到的字段，all_fields为true时其余字段仍然按各自的选项输出
+fn get_printed_fields<'a>(
+    st: &'a syn::DeriveInput,
+    struct_fmt: Option<&syn::LitStr>,
+    entries: &[Entry],
+    all_fields: bool,
+) -> syn::Result<PrintedFields<'a>> {
+    let mut printed = PrintedFields {
+        fields: Vec::new(),
+        limited_fields: Vec::new(),
+        formatted_fields: Vec::new(),
+        bounds: Vec::new(),
+        uses_debug_with: false,
+        uses_debug_limit: false,
+        uses_debug_bytes: false,
+        uses_debug_ptr: false,
+    };
+    for fields in get_all_fields_from_derive_input(st)? {
+        if let Some(fmt_lit) = struct_fmt {
+            for (idx, format_trait) in parse_format_string(fmt_lit, fields, false)?.traits {
+                printed.push_formatted(format_trait, fields.iter().nth(idx).unwrap());
+            }
+            if !all_fields {
+                continue;
+            }
+        }
+        // entry只能用在结构体上，枚举上的在生成时报错
+        if let syn::Data::Struct(_) = st.data {
+            for entry in entries {
+                for (idx, format_trait) in parse_format_string(&entry.fmt, fields, false)?.traits {
+                    printed.push_formatted(format_trait, fields.iter().nth(idx).unwrap());
+                }
+            }
+        }
+        for field in fields.iter() {
+            let options = get_field_options(field)?;
+            // 有格式化字符串的字段，自身按位置参数的格式说明推断约束，没有位置参数时自身不输出
+            let mut positional_traits = vec!["Debug"];
+            if let (true, Some(format)) = (options.uses_debug(), &options.format) {
+                let format = parse_format_string(format, fields, true)?;
+                for (idx, format_trait) in format.traits {
+                    printed.push_formatted(format_trait, fields.iter().nth(idx).unwrap());
+                }
+                positional_traits = format.positional_traits;
+            }
+            printed.uses_debug_with |= !options.skip && options.with.is_some();
+            printed.uses_debug_limit |= options.uses_debug() && options.limit.is_some();
+            printed.uses_debug_bytes |= !options.skip && options.bytes.is_some();
+            printed.uses_debug_ptr |= !options.skip && options.ptr.is_some();
+            if let Some(bound) = options.bound {
+                printed.bounds.extend(bound);
+            } else if options.uses_debug() && options.limit.is_some() {
+                printed.limited_fields.push(field);
+            } else if options.uses_debug() {
+                for format_trait in positional_traits {
+                    printed.push_formatted(format_trait, field);
+                }
+            }
+        }
+    }
+
+    Ok(printed)
+}
+
+impl<'a> PrintedFields<'a> {
+    fn push_formatted(&mut self, format_trait: &'static str, field: &'a Field) {
+        if format_trait == "Debug" {
+            self.fields.push(field);
+        } else {
+            self.formatted_fields.push((format_trait, field));
+        }
+    }
+}
 
-    if let Some(hatch) = get_struct_escape_hatch(st) {
-        generics.make_where_clause();
+// 有结构体上的#[debug(bound = "...")]时只使用它，否则根据输出的字段推断Debug约束
+fn add_debug_bounds(
+    st: &syn::DeriveInput,
+    generics: &mut syn::Generics,
+    hatch: Option<Vec<syn::WherePredicate>>,
+    printed: PrintedFields,
+) {
+    if let Some(hatch) = hatch {
+        generics.make_where_clause().predicates.extend(hatch);
+    } else {
+        add_inferred_bounds(
+            generics,
+            get_type_param_usage(st, &printed.fields, true),
+            &syn::parse_quote!(::core::fmt::Debug),
+        );
+        add_inferred_bounds(
+            generics,
+            get_type_param_usage(st, &printed.limited_fields, false),
+            &syn::parse_quote!(::core::fmt::Debug),
+        );
+        add_format_trait_bounds(st, generics, &printed.formatted_fields);
         generics
-            .where_clause
-            .as_mut()
-            .unwrap()
+            .make_where_clause()
             .predicates
-            .push(syn::parse_str(hatch.as_str()).unwrap());
-    } else {
-        for param in generics.params.iter_mut() {
-            if let syn::GenericParam::Type(t) = param {
-                let type_param_name = t.ident.to_string();
+            .extend(printed.bounds);
+    }
+}
 
-                if phantomdadta_type_param_names.contains(&type_param_name)
-                    && !field_type_names.contains(&type_param_name)
-                {
-                    continue;
+// 输出字段时用到的包装类型，只在用到时定义
+fn generate_helper_defs(printed: &PrintedFields) -> proc_macro2::TokenStream {
+    let mut helper_defs = proc_macro2::TokenStream::new();
+    // #[debug(with = "...")]的字段通过这个包装类型调用用户提供的格式化函数
+    if printed.uses_debug_with {
+        helper_defs.extend(quote::quote! {
+            struct __DebugWith<'a, T: ?Sized>(
+                &'a T,
+                fn(&T, &mut ::core::fmt::Formatter) -> ::core::fmt::Result,
+            );
+
+            impl<'a, T: ?Sized> ::core::fmt::Debug for __DebugWith<'a, T> {
+                fn fmt(&self, fmt: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                    (self.1)(self.0, fmt)
                 }
+            }
+        });
+    }
+    // #[debug(limit = N)]的字段只输出前N个元素，剩下的只输出数量
+    if printed.uses_debug_limit {
+        helper_defs.extend(quote::quote! {
+            struct __DebugLimit<'a, T: ?Sized>(&'a T, ::core::primitive::usize);
 
-                // 如果是关联类型，就不要对泛型参数`T`本身再添加约束了,除非`T`本身也被直接使用了
-                if associated_types_map.contains_key(&type_param_name)
-                    && !field_type_names.contains(&type_param_name)
-                {
-                    continue;
+            impl<'a, T: ?Sized> ::core::fmt::Debug for __DebugLimit<'a, T>
+            where
+                &'a T: ::core::iter::IntoIterator,
+                <&'a T as ::core::iter::IntoIterator>::Item: ::core::fmt::Debug,
+            {
+                fn fmt(&self, fmt: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                    let mut list = fmt.debug_list();
+                    let mut iter = ::core::iter::IntoIterator::into_iter(self.0);
+                    list.entries(iter.by_ref().take(self.1));
+                    let rest = iter.count();
+                    if rest > 0 {
+                        list.entry(&::core::format_args!("... ({} more)", rest));
+                    }
+                    list.finish()
                 }
+            }
+        });
+    }
+    // #[debug(hex)]和#[debug(ascii)]的字段按字节输出在一行里，同样支持limit
+    if printed.uses_debug_bytes {
+        helper_defs.extend(quote::quote! {
+            struct __DebugBytes<'a>(&'a [::core::primitive::u8], ::core::primitive::bool, ::core::primitive::usize);
 
-                // 为泛型 T 添加限制
-                t.bounds.push(syn::parse_quote!(std::fmt::Debug));
+            impl<'a> ::core::fmt::Debug for __DebugBytes<'a> {
+                fn fmt(&self, fmt: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                    let shown = &self.0[..::core::cmp::min(self.0.len(), self.2)];
+                    if self.1 {
+                        for byte in shown {
+                            ::core::write!(fmt, "{:02x}", byte)?;
+                        }
+                    } else {
+                        fmt.write_str("b\"")?;
+                        for byte in shown {
+                            ::core::write!(fmt, "{}", ::core::ascii::escape_default(*byte))?;
+                        }
+                        fmt.write_str("\"")?;
+                    }
+                    if self.0.len() > shown.len() {
+                        ::core::write!(fmt, "... ({} more)", self.0.len() - shown.len())?;
+                    }
+                    ::core::result::Result::Ok(())
+                }
+            }
+        });
+    }
+    // #[debug(ptr)]的字段通过这个包装类型用fmt::Pointer输出
+    if printed.uses_debug_ptr {
+        helper_defs.extend(quote::quote! {
+            struct __DebugPtr<'a, T: ?Sized>(&'a T);
+
+            impl<'a, T: ::core::fmt::Pointer + ?Sized> ::core::fmt::Debug for __DebugPtr<'a, T> {
+                fn fmt(&self, fmt: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                    ::core::fmt::Pointer::fmt(self.0, fmt)
+                }
+            }
+        });
+    }
+
+    helper_defs
+}
+
+// 字段类型是否写成了Option<...>，包括std::option::Option<...>
+fn is_option_type(ty: &syn::Type) -> bool {
+    if let syn::Type::Path(syn::TypePath { qself: None, path }) = ty {
+        let segment = path.segments.last().unwrap();
+        if let syn::PathArguments::AngleBracketed(ref args) = segment.arguments {
+            return segment.ident == "Option" && args.args.len() == 1;
+        }
+    }
+    false
+}
+
+// #[debug(max_depth = N)]：用线程局部的计数器记录当前线程中这个类型嵌套输出的层数，
+// 超过N层时只输出`...`，计数器在返回或panic时由__DepthGuard恢复，因此需要std
+fn generate_depth_guard(max_depth: usize) -> proc_macro2::TokenStream {
+    quote::quote! {
+        ::std::thread_local! {
+            static __DEPTH: ::core::cell::Cell<::core::primitive::usize> = ::core::cell::Cell::new(0);
+        }
+
+        struct __DepthGuard;
+
+        impl ::core::ops::Drop for __DepthGuard {
+            fn drop(&mut self) {
+                __DEPTH.with(|depth| depth.set(depth.get() - 1));
             }
         }
-        generics.make_where_clause();
-        // 关联类型的约束要放到where子句里
-        for (_, associated_types) in associated_types_map {
-            for associated_type in associated_types {
-                generics
-                    .where_clause
-                    .as_mut()
-                    .unwrap()
-                    .predicates
-                    .push(syn::parse_quote!(#associated_type:std::fmt::Debug));
+
+        let __depth = __DEPTH.with(|depth| {
+            let current = depth.get();
+            depth.set(current + 1);
+            current
+        });
+        let __guard = __DepthGuard;
+        if __depth >= #max_depth {
+            return fmt.write_str("...");
+        }
+    }
+}
+
+// 不输出任何字段，只输出类型名和大小，#[debug(opaque(hex))]时再输出所有字节
+fn generate_opaque_debug(
+    st: &syn::DeriveInput,
+    struct_options: StructOptions,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let struct_name_ident = &st.ident;
+    if struct_options.transparent.is_some()
+        || struct_options.fmt.is_some()
+        || struct_options.reflect
+        || struct_options.non_exhaustive != NonExhaustive::Never
+        || !struct_options.entries.is_empty()
+    {
+        return Err(syn::Error::new_spanned(
+            st,
+            "`debug(opaque)` cannot be combined with `debug(transparent)`, `debug(fmt = \"...\")`, `debug(reflect)`, `debug(non_exhaustive)` or `debug(entry(...))`",
+        ));
+    }
+
+    // 不输出变体，变体上的选项也就没有作用
+    if let syn::Data::Enum(syn::DataEnum { ref variants, .. }) = st.data {
+        for variant in variants.iter() {
+            if let Some(name) = get_variant_name(variant)? {
+                return Err(syn::Error::new_spanned(
+                    name,
+                    "`debug(name = \"...\")` on a variant cannot be combined with `debug(opaque)`",
+                ));
             }
         }
     }
 
+    let name_literal = match struct_options.name {
+        Some(ref name) => name.value(),
+        None => struct_name_ident.to_string(),
+    };
+    let (helper_defs, bytes_stmt) = if let Some(Opaque::Hex) = struct_options.opaque {
+        let helper_defs = generate_helper_defs(&PrintedFields {
+            fields: Vec::new(),
+            limited_fields: Vec::new(),
+            formatted_fields: Vec::new(),
+            bounds: Vec::new(),
+            uses_debug_with: false,
+            uses_debug_limit: false,
+            uses_debug_bytes: true,
+            uses_debug_ptr: false,
+        });
+        let checks = generate_plain_bytes_checks(st)?;
+        let bytes_stmt = quote::quote! {
+            #checks
+            // 上面的检查保证了每个字节都已经初始化
+            let __bytes = unsafe {
+                ::core::slice::from_raw_parts(
+                    self as *const Self as *const ::core::primitive::u8,
+                    ::core::mem::size_of::<Self>(),
+                )
+            };
+            __debug.field(
+                "bytes",
+                &__DebugBytes(__bytes, true, ::core::primitive::usize::MAX),
+            );
+        };
+        (helper_defs, bytes_stmt)
+    } else {
+        (
+            proc_macro2::TokenStream::new(),
+            proc_macro2::TokenStream::new(),
+        )
+    };
+
+    // 不输出任何字段，也就不需要推断约束
+    let mut generics = st.generics.clone();
+    if let Some(hatch) = struct_options.bound {
+        generics.make_where_clause().predicates.extend(hatch);
+    }
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
     let ret_stream = quote::quote! {
-        impl #impl_generics std::fmt::Debug for #struct_name_ident #ty_generics #where_clause{
-            fn fmt(&self,fmt:&mut std::fmt::Formatter)->std::fmt::Result{
-                #fmt_body_stream
+        impl #impl_generics ::core::fmt::Debug for #struct_name_ident #ty_generics #where_clause{
+            fn fmt(&self,fmt:&mut ::core::fmt::Formatter)->::core::fmt::Result{
+                #helper_defs
+                let mut __debug = fmt.debug_struct(#name_literal);
+                __debug.field("size", &::core::mem::size_of::<Self>());
+                #bytes_stmt
+                __debug.finish()
             }
         }
     };
@@ -119,159 +551,2037 @@ fn generate_debug_trait(st: &syn::DeriveInput) -> syn::Result<proc_macro2::Token
     Ok(ret_stream)
 }
 
-//获取字段从 deriveInput
-fn get_fields_from_derive_input(st: &syn::DeriveInput) -> syn::Result<&StructFields> {
-    if let syn::Data::Struct(syn::DataStruct {
-        fields: syn::Fields::Named(syn::FieldsNamed { ref named, .. }),
-        ..
-    }) = st.data
-    {
-        return Ok(named);
+// #[debug(opaque(hex))]读取值的所有字节，只有每个字节都已初始化时才是安全的，否则是未定义行为。
+// 因此要求每个字段都是没有填充字节的基本类型或其数组，并在编译期断言整个值没有填充字节：
+// 结构体的字段大小之和等于结构体的大小，联合体的每个字段都和联合体一样大
+fn generate_plain_bytes_checks(st: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let struct_name_ident = &st.ident;
+    let (fields, is_union): (Vec<&syn::Field>, bool) = match st.data {
+        syn::Data::Struct(syn::DataStruct { ref fields, .. }) => (fields.iter().collect(), false),
+        syn::Data::Union(syn::DataUnion { ref fields, .. }) => {
+            (fields.named.iter().collect(), true)
+        }
+        syn::Data::Enum(_) => {
+            return Err(syn::Error::new_spanned(
+                st,
+                "`debug(opaque(hex))` is not supported on enums, their bytes can be uninitialized",
+            ))
+        }
+    };
+    // 断言写在函数内的常量里，无法引用外层impl的泛型参数
+    if !st.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &st.generics,
+            "`debug(opaque(hex))` is not supported on generic types",
+        ));
     }
-    Err(syn::Error::new_spanned(
-        st,
-        "Must define on a Struct,not Enum".to_string(),
-    ))
+
+    let mut checks = proc_macro2::TokenStream::new();
+    let mut sizes = Vec::new();
+    for field in fields {
+        let ty = &field.ty;
+        let plain_ty = get_plain_bytes_type(ty).ok_or_else(|| {
+            syn::Error::new_spanned(
+                ty,
+                "`debug(opaque(hex))` requires every field to be an integer, float, `bool`, `char` or an array of them",
+            )
+        })?;
+        // 字段类型可能是同名的类型别名，用函数指针的转换确认它就是对应的基本类型
+        checks.extend(quote::quote! {
+            const _: fn(#ty) -> #plain_ty = |__value| __value;
+        });
+        sizes.push(quote::quote!(::core::mem::size_of::<#plain_ty>()));
+    }
+    let size = quote::quote!(::core::mem::size_of::<#struct_name_ident>());
+    if is_union {
+        checks.extend(quote::quote! {
+            const _: () = ::core::assert!(
+                #(#size == #sizes)&&*,
+                "`debug(opaque(hex))` requires every union field to be as large as the union",
+            );
+        });
+    } else {
+        checks.extend(quote::quote! {
+            const _: () = ::core::assert!(
+                #size == 0 #(+ #sizes)*,
+                "`debug(opaque(hex))` requires a struct without padding bytes",
+            );
+        });
+    }
+    Ok(checks)
 }
 
-fn get_custom_format_of_field(field: &syn::Field) -> syn::Result<Option<String>> {
-    for attr in field.attrs.iter() {
-        if let syn::Meta::NameValue(syn::MetaNameValue {
-            ref path, ref lit, ..
-        }) = attr.parse_meta()?
-        {
-            if path.is_ident("debug") {
-                if let syn::Lit::Str(ref lit) = lit {
-                    return Ok(Some(lit.value()));
+// 没有填充字节、每个字节都有效的类型，返回用::core::primitive写出的同一类型
+fn get_plain_bytes_type(ty: &syn::Type) -> Option<proc_macro2::TokenStream> {
+    match ty {
+        syn::Type::Paren(syn::TypeParen { ref elem, .. })
+        | syn::Type::Group(syn::TypeGroup { ref elem, .. }) => get_plain_bytes_type(elem),
+        syn::Type::Array(syn::TypeArray {
+            ref elem, ref len, ..
+        }) => {
+            let elem = get_plain_bytes_type(elem)?;
+            Some(quote::quote!([#elem; #len]))
+        }
+        syn::Type::Path(syn::TypePath { qself: None, path }) => {
+            let ident = path.get_ident()?;
+            match ident.to_string().as_str() {
+                "bool" | "char" | "f32" | "f64" | "u8" | "u16" | "u32" | "u64" | "u128"
+                | "usize" | "i8" | "i16" | "i32" | "i64" | "i128" | "isize" => {
+                    Some(quote::quote!(::core::primitive::#ident))
                 }
-            } else {
-                return Err(syn::Error::new_spanned(field, r#"expected `debug=...`"#));
+                _ => None,
             }
         }
+        _ => None,
     }
-
-    Ok(None)
 }
 
-fn get_phantomdata_generic_type_name(f: &syn::Field) -> syn::Result<Option<String>> {
-    if let syn::Type::Path(syn::TypePath {
-        path: syn::Path { ref segments, .. },
-        ..
-    }) = f.ty
-    {
-        let ps = segments.first();
-
-        if let Some(syn::PathSegment {
-            ref ident,
-            ref arguments,
-        }) = ps
-        {
-            if ident.eq("PhantomData") {
-                if let syn::PathArguments::AngleBracketed(syn::AngleBracketedGenericArguments {
-                    ref args,
-                    ..
-                }) = arguments
-                {
-                    if let Some(syn::GenericArgument::Type(syn::Type::Path(syn::TypePath {
-                        ref path,
-                        ..
-                    }))) = args.first()
-                    {
-                        let ps = path.segments.first();
-                        if let Some(syn::PathSegment { ref ident, .. }) = ps {
-                            return Ok(Some(ident.to_string()));
-                        }
+// debug_fields()的函数体：按Debug输出的规则收集当前值（枚举则是当前变体）的每个字段
+// 自定义了输出方式的字段没有可以借用的值，所以统一装箱，因此这个函数需要std
+fn generate_debug_fields_body(
+    st: &syn::DeriveInput,
+    entries: &[Entry],
+    helper_defs: &mut proc_macro2::TokenStream,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let mut uses_debug_fn = false;
+    let mut arms = Vec::new();
+    let groups: Vec<(proc_macro2::TokenStream, &syn::Fields)> = match st.data {
+        syn::Data::Struct(syn::DataStruct { ref fields, .. }) => {
+            let struct_name_ident = &st.ident;
+            vec![(quote::quote!(#struct_name_ident), fields)]
+        }
+        syn::Data::Enum(syn::DataEnum { ref variants, .. }) => variants
+            .iter()
+            .map(|v| {
+                let variant_ident = &v.ident;
+                (quote::quote!(Self::#variant_ident), &v.fields)
+            })
+            .collect(),
+        syn::Data::Union(_) => unreachable!(),
+    };
+
+    for (path, fields) in groups {
+        let mut field_stmts = Vec::new();
+        for (idx, field) in fields.iter().enumerate() {
+            let options = get_field_options(field)?;
+            if options.skip {
+                continue;
+            }
+
+            let binding = quote::format_ident!("__self_{}", idx);
+            let name = match (&options.rename, &field.ident) {
+                (Some(rename), _) => rename.value(),
+                (None, Some(ident)) => ident.to_string(),
+                (None, None) => idx.to_string(),
+            };
+            // 没有自定义输出方式时直接借用字段，保留格式化选项
+            let value =
+                if options.format.is_none() && options.limit.is_none() && options.uses_debug() {
+                    quote::quote!(::std::boxed::Box::new(#binding))
+                } else {
+                    uses_debug_fn = true;
+                    let value = generate_field_value(&binding, &field.ty, &options, fields)?;
+                    quote::quote! {
+                        ::std::boxed::Box::new(__DebugFn(move |fmt: &mut ::core::fmt::Formatter| {
+                            ::core::fmt::Debug::fmt(&#value, fmt)
+                        }))
                     }
-                }
+                };
+            let mut field_stmt = quote::quote! {
+                __fields.push((#name, #value));
+            };
+            if let Some(skip_if) = options.skip_if {
+                field_stmt = quote::quote! {
+                    if !#skip_if(#binding) {
+                        #field_stmt
+                    }
+                };
             }
+            field_stmts.push(field_stmt);
         }
+        // entry只会出现在结构体上，枚举在生成Debug时已经报错
+        for entry in entries {
+            uses_debug_fn = true;
+            let name = &entry.name;
+            let format_args =
+                generate_format_args(&parse_format_string(&entry.fmt, fields, false)?, None);
+            field_stmts.push(quote::quote! {
+                __fields.push((#name, ::std::boxed::Box::new(__DebugFn(move |fmt: &mut ::core::fmt::Formatter| {
+                    fmt.write_fmt(#format_args)
+                }))));
+            });
+        }
+
+        let pattern = generate_fields_pattern(fields);
+        arms.push(quote::quote! {
+            #path #pattern => {
+                let mut __fields: ::std::vec::Vec<(&'static str, ::std::boxed::Box<dyn ::core::fmt::Debug + '_>)> =
+                    ::std::vec::Vec::new();
+                #(#field_stmts)*
+                __fields
+            }
+        });
+    }
+
+    if uses_debug_fn {
+        helper_defs.extend(quote::quote! {
+            struct __DebugFn<F>(F);
+
+            impl<F> ::core::fmt::Debug for __DebugFn<F>
+            where
+                F: ::core::ops::Fn(&mut ::core::fmt::Formatter) -> ::core::fmt::Result,
+            {
+                fn fmt(&self, fmt: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                    (self.0)(fmt)
+                }
+            }
+        });
     }
-    Ok(None)
+
+    let fields_stream = if arms.is_empty() {
+        quote::quote!(match *self {})
+    } else {
+        quote::quote! {
+            match self {
+                #(#arms)*
+            }
+        }
+    };
+
+    // 没有任何变体的枚举匹配不到任何分支，要标注类型才能推断出返回的迭代器
+    Ok(quote::quote! {
+        let __fields: ::std::vec::Vec<(&'static str, ::std::boxed::Box<dyn ::core::fmt::Debug + '_>)> =
+            #fields_stream;
+        ::core::iter::IntoIterator::into_iter(__fields)
+    })
 }
 
-fn get_field_type_name(f: &syn::Field) -> syn::Result<Option<String>> {
-    if let syn::Type::Path(syn::TypePath {
-        path: syn::Path { ref segments, .. },
-        ..
-    }) = f.ty
-    {
-        if let Some(syn::PathSegment { ref ident, .. }) = segments.first() {
-            return Ok(Some(ident.to_string()));
+// 按格式说明把格式化字符串引用到的字段分到各自的格式化trait下，分别推断约束
+fn add_format_trait_bounds(
+    st: &syn::DeriveInput,
+    generics: &mut syn::Generics,
+    formatted_fields: &[(&'static str, &Field)],
+) {
+    let mut fields_by_trait: Vec<(&'static str, Vec<&Field>)> = Vec::new();
+    for &(format_trait, field) in formatted_fields {
+        match fields_by_trait.iter_mut().find(|(t, _)| *t == format_trait) {
+            Some((_, fields)) => fields.push(field),
+            None => fields_by_trait.push((format_trait, vec![field])),
         }
     }
-    Ok(None)
+    for (format_trait, fields) in fields_by_trait {
+        let format_trait = syn::Ident::new(format_trait, proc_macro2::Span::call_site());
+        add_inferred_bounds(
+            generics,
+            get_type_param_usage(st, &fields, true),
+            &syn::parse_quote!(::core::fmt::#format_trait),
+        );
+    }
 }
 
-struct TypePathVisit {
-    generic_type_names: Vec<String>,
-    associated_types: HashMap<String, Vec<syn::TypePath>>,
+// 根据泛型参数在字段类型中出现的位置，为它们加上bound这个trait的约束
+fn add_inferred_bounds(generics: &mut syn::Generics, usage: TypePathVisit, bound: &syn::Path) {
+    for param in generics.params.iter_mut() {
+        if let syn::GenericParam::Type(t) = param {
+            // 只有直接以`T`的形式出现在字段类型中的泛型参数才需要约束
+            // 只出现在PhantomData、函数指针、关联类型等位置的不需要
+            if usage.bare_type_params.contains(&t.ident) {
+                // 为泛型 T 添加限制
+                t.bounds.push(syn::parse_quote!(#bound));
+            }
+        }
+    }
+    // 关联类型以及整个字段类型的约束要放到where子句里
+    let where_clause = generics.make_where_clause();
+    for associated_type in usage.associated_types {
+        where_clause
+            .predicates
+            .push(syn::parse_quote!(#associated_type: #bound));
+    }
+    for field_type in usage.field_types {
+        where_clause
+            .predicates
+            .push(syn::parse_quote!(#field_type: #bound));
+    }
 }
 
-impl<'ast> Visit<'ast> for TypePathVisit {
-    fn visit_type_path(&mut self, node: &'ast syn::TypePath) {
-        if node.path.segments.len() >= 2 {
-            let generic_type_name = node.path.segments[0].ident.to_string();
-            if self.generic_type_names.contains(&generic_type_name) {
-                self.associated_types
-                    .entry(generic_type_name)
-                    .or_insert(Vec::new())
-                    .push(node.clone())
-            }
+//获取字段从 deriveInput，枚举则是每个变体各自的字段，用于推断泛型约束
+fn get_all_fields_from_derive_input(st: &syn::DeriveInput) -> syn::Result<Vec<&syn::Fields>> {
+    match st.data {
+        syn::Data::Struct(syn::DataStruct { ref fields, .. }) => Ok(vec![fields]),
+        syn::Data::Enum(syn::DataEnum { ref variants, .. }) => {
+            Ok(variants.iter().map(|v| &v.fields).collect())
         }
-        // Visit 模式要求在当前节点访问完成后，继续调用默认实现的visit方法，从而遍历到所有的
-        // 必须调用这个函数，否则遍历到这个节点就不再往更深层走了
-        visit::visit_type_path(self, node)
+        syn::Data::Union(_) => Err(syn::Error::new_spanned(
+            st,
+            "Must define on a Struct or Enum, not Union".to_string(),
+        )),
     }
 }
 
-fn get_generic_associated_types(st: &syn::DeriveInput) -> HashMap<String, Vec<syn::TypePath>> {
-    // 找出泛型
-    let origin_generic_params: Vec<String> = st
-        .generics
-        .params
-        .iter()
-        .filter_map(|f| {
-            if let syn::GenericParam::Type(ty) = f {
-                return Some(ty.ident.to_string());
-            } else {
-                None
+// 为一组字段生成解构模式，以及输出这些字段的debug_struct/debug_tuple调用
+// 字段统一绑定为__self_0、__self_1...，避免字段名与生成代码中的变量重名
+fn generate_fields_debug(
+    name_literal: &str,
+    fields: &syn::Fields,
+    non_exhaustive: NonExhaustive,
+    entries: &[Entry],
+) -> syn::Result<(proc_macro2::TokenStream, proc_macro2::TokenStream)> {
+    let mut field_stmts = Vec::new();
+    // 是否有字段一定不会输出，以及是否有字段可能在运行时被跳过
+    let mut has_skipped = false;
+    let mut has_skip_if = false;
+    for (idx, field) in fields.iter().enumerate() {
+        let options = get_field_options(field)?;
+        if options.skip {
+            has_skipped = true;
+            continue;
+        }
+
+        let binding = quote::format_ident!("__self_{}", idx);
+        let value = generate_field_value(&binding, &field.ty, &options, fields)?;
+
+        let mut field_stmt = match (&field.ident, options.rename) {
+            (Some(_), Some(rename)) => quote::quote! {
+                __debug.field(#rename, &#value);
+            },
+            (Some(field_name_ident), None) => {
+                let field_name_literal = field_name_ident.to_string();
+                quote::quote! {
+                    __debug.field(#field_name_literal, &#value);
+                }
+            }
+            (None, Some(rename)) => {
+                return Err(syn::Error::new(
+                    rename.span(),
+                    "`debug(rename = \"...\")` is only supported on named fields",
+                ))
             }
-        })
-        .collect();
+            (None, None) => quote::quote! {
+                __debug.field(&#value);
+            },
+        };
+        if let Some(skip_if) = options.skip_if {
+            // 只有需要根据是否跳过了字段决定输出`..`时，才记录运行时跳过的字段
+            let record_skipped = if non_exhaustive == NonExhaustive::WhenSkipped {
+                quote::quote!(else { __skipped = true; })
+            } else {
+                proc_macro2::TokenStream::new()
+            };
+            field_stmt = quote::quote! {
+                if !#skip_if(#binding) {
+                    #field_stmt
+                } #record_skipped
+            };
+            has_skip_if = true;
+        }
+        field_stmts.push(field_stmt);
+    }
+    if let (Some(entry), false) = (entries.first(), matches!(fields, syn::Fields::Named(_))) {
+        return Err(syn::Error::new_spanned(
+            &entry.name,
+            "`debug(entry(...))` is only supported on structs with named fields",
+        ));
+    }
+    for entry in entries {
+        let name = &entry.name;
+        let format_args =
+            generate_format_args(&parse_format_string(&entry.fmt, fields, false)?, None);
+        field_stmts.push(quote::quote! {
+            __debug.field(#name, &#format_args);
+        });
+    }
 
-    // 根据泛型去找是否关联的其他类型
-    let mut visitor = TypePathVisit {
-        generic_type_names: origin_generic_params,
-        associated_types: HashMap::new(),
+    // 输出结尾：是否以`..`结束，可能要等到运行时才能确定
+    let (skipped_def, finish) = match non_exhaustive {
+        NonExhaustive::Always => (
+            proc_macro2::TokenStream::new(),
+            quote::quote!(__debug.finish_non_exhaustive()),
+        ),
+        NonExhaustive::WhenSkipped if has_skipped => (
+            proc_macro2::TokenStream::new(),
+            quote::quote!(__debug.finish_non_exhaustive()),
+        ),
+        NonExhaustive::WhenSkipped if has_skip_if => (
+            quote::quote!(let mut __skipped = false;),
+            quote::quote! {
+                if __skipped {
+                    __debug.finish_non_exhaustive()
+                } else {
+                    __debug.finish()
+                }
+            },
+        ),
+        _ => (
+            proc_macro2::TokenStream::new(),
+            quote::quote!(__debug.finish()),
+        ),
+    };
+
+    let pattern = generate_fields_pattern(fields);
+    let ret = match fields {
+        syn::Fields::Named(_) => (
+            pattern,
+            quote::quote!({
+                let mut __debug = fmt.debug_struct(#name_literal);
+                #skipped_def
+                #(#field_stmts)*
+                #finish
+            }),
+        ),
+        syn::Fields::Unnamed(_) => (
+            pattern,
+            quote::quote!({
+                let mut __debug = fmt.debug_tuple(#name_literal);
+                #skipped_def
+                #(#field_stmts)*
+                #finish
+            }),
+        ),
+        // 单元结构体没有字段，只有标记为总是输出`..`时才需要debug_struct
+        syn::Fields::Unit if non_exhaustive == NonExhaustive::Always => (
+            pattern,
+            quote::quote!(fmt.debug_struct(#name_literal).finish_non_exhaustive()),
+        ),
+        syn::Fields::Unit => (pattern, quote::quote!(fmt.write_str(#name_literal))),
     };
 
-    visitor.visit_derive_input(st);
+    Ok(ret)
+}
+
+// 解构一组字段的模式，每个字段都绑定为__self_N，格式化字符串可能引用任何一个字段
+fn generate_fields_pattern(fields: &syn::Fields) -> proc_macro2::TokenStream {
+    let bindings = (0..fields.len()).map(|idx| quote::format_ident!("__self_{}", idx));
+    match fields {
+        syn::Fields::Named(_) => {
+            let field_name_idents = fields.iter().map(|f| &f.ident);
+            quote::quote!({ #(#field_name_idents: #bindings),* })
+        }
+        syn::Fields::Unnamed(_) => quote::quote!(( #(#bindings),* )),
+        syn::Fields::Unit => proc_macro2::TokenStream::new(),
+    }
+}
+
+// 输出单个字段时实际交给Debug的值，binding是该字段的引用，ty是字段的类型
+fn generate_field_value(
+    binding: &syn::Ident,
+    ty: &syn::Type,
+    options: &FieldOptions,
+    fields: &syn::Fields,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let ret = match options.redact {
+        Some(Redact::Placeholder(ref placeholder)) => {
+            quote::quote!(::core::format_args!("{}", #placeholder))
+        }
+        Some(Redact::Len) => {
+            quote::quote!(::core::format_args!("<redacted {} bytes>", #binding.len()))
+        }
+        None if options.ptr == Some(Ptr::Pointer) => quote::quote!(__DebugPtr(#binding)),
+        None if options.ptr == Some(Ptr::Option) => quote::quote! {
+            ::core::option::Option::map(::core::option::Option::as_ref(#binding), __DebugPtr)
+        },
+        None => match (&options.with, options.bytes, options.limit, &options.format) {
+            (Some(with), ..) => quote::quote!(__DebugWith(#binding, #with)),
+            (None, Some(bytes), limit, _) => {
+                let hex = bytes == Bytes::Hex;
+                let limit = match limit {
+                    Some(limit) => quote::quote!(#limit),
+                    None => quote::quote!(::core::primitive::usize::MAX),
+                };
+                quote::quote! {
+                    __DebugBytes(::core::convert::AsRef::<[::core::primitive::u8]>::as_ref(#binding), #hex, #limit)
+                }
+            }
+            (None, None, Some(limit), _) => {
+                let target = generate_limit_target(quote::quote!(#binding), ty);
+                quote::quote!(__DebugLimit(#target, #limit))
+            }
+            (None, None, None, Some(format)) => {
+                let format = parse_format_string(format, fields, true)?;
+                generate_format_args(&format, Some(binding))
+            }
+            (None, None, None, None) => quote::quote!(::core::format_args!("{:?}", #binding)),
+        },
+    };
 
-    return visitor.associated_types;
+    Ok(ret)
 }
 
-fn get_struct_escape_hatch(st: &syn::DeriveInput) -> Option<String> {
-    if let Some(attr) = st.attrs.last() {
-        if let Ok(syn::Meta::List(syn::MetaList {
+// limit要遍历的是集合本身的引用，&[T]、&Vec<T>、Box<[T]>这样的字段的引用并不能遍历，
+// 所以先解引用到里面的集合，expr是类型为&ty的表达式
+fn generate_limit_target(
+    expr: proc_macro2::TokenStream,
+    ty: &syn::Type,
+) -> proc_macro2::TokenStream {
+    match ty {
+        syn::Type::Reference(syn::TypeReference { ref elem, .. }) => {
+            generate_limit_target(quote::quote!(&**#expr), elem)
+        }
+        syn::Type::Paren(syn::TypeParen { ref elem, .. })
+        | syn::Type::Group(syn::TypeGroup { ref elem, .. }) => generate_limit_target(expr, elem),
+        syn::Type::Path(syn::TypePath {
+            qself: None,
             ref path,
-            ref nested,
-            ..
-        })) = attr.parse_meta()
-        {
-            if path.is_ident("debug") {
-                if let Some(syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
-                    lit: syn::Lit::Str(ref lit),
-                    ref path,
-                    ..
-                }))) = nested.first()
+        }) => {
+            let segment = path.segments.last().unwrap();
+            match segment.arguments {
+                syn::PathArguments::AngleBracketed(ref args)
+                    if args.args.len() == 1
+                        && (segment.ident == "Box"
+                            || segment.ident == "Rc"
+                            || segment.ident == "Arc") =>
                 {
-                    if path.is_ident("bound") {
-                        return Some(lit.value());
+                    match args.args.first() {
+                        Some(syn::GenericArgument::Type(elem)) => {
+                            generate_limit_target(quote::quote!(&**#expr), elem)
+                        }
+                        _ => expr,
                     }
                 }
+                _ => expr,
+            }
+        }
+        _ => expr,
+    }
+}
+
+// 用改写后的格式化字符串生成format_args!调用，引用到的字段作为同名的命名参数传入
+fn generate_format_args(
+    format: &FormatString,
+    positional: Option<&syn::Ident>,
+) -> proc_macro2::TokenStream {
+    let format_str = &format.value;
+    let positional = match positional {
+        Some(binding) if format.uses_positional => quote::quote!(#binding,),
+        _ => proc_macro2::TokenStream::new(),
+    };
+    let named = format
+        .fields
+        .iter()
+        .map(|idx| quote::format_ident!("__self_{}", idx));
+    quote::quote!(::core::format_args!(#format_str, #positional #(#named = #named),*))
+}
+
+// 改写后的格式化字符串，字段引用都换成了__self_N
+struct FormatString {
+    value: String,
+    // 引用到的字段下标，不重复
+    fields: Vec<usize>,
+    // 引用到的字段下标，以及输出时用到的格式化trait，如Display、Debug、LowerHex
+    traits: Vec<(usize, &'static str)>,
+    // 是否还有{}、{:?}、{0}这样引用字段本身的位置参数
+    uses_positional: bool,
+    // 位置参数用到的格式化trait，不重复
+    positional_traits: Vec<&'static str>,
+}
+
+// 解析格式化字符串中的字段引用：{name}、{self.name}、{self.0}，都可以带:?等格式说明
+// has_positional为false时没有位置参数可用，{0}直接表示元组结构体的第0个字段
+fn parse_format_string(
+    lit: &syn::LitStr,
+    fields: &syn::Fields,
+    has_positional: bool,
+) -> syn::Result<FormatString> {
+    let source = lit.value();
+    let mut ret = FormatString {
+        value: String::new(),
+        fields: Vec::new(),
+        traits: Vec::new(),
+        uses_positional: false,
+        positional_traits: Vec::new(),
+    };
+
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '{' {
+            ret.value.push(c);
+            continue;
+        }
+        if chars.peek() == Some(&'{') {
+            chars.next();
+            ret.value.push_str("{{");
+            continue;
+        }
+
+        let mut placeholder = String::new();
+        loop {
+            match chars.next() {
+                Some('}') => break,
+                Some(c) => placeholder.push(c),
+                None => {
+                    return Err(syn::Error::new_spanned(
+                        lit,
+                        format!("invalid format string `{}`: expected `}}`", source),
+                    ))
+                }
             }
         }
+        let (arg, spec) = match placeholder.find(':') {
+            Some(pos) => placeholder.split_at(pos),
+            None => (placeholder.as_str(), ""),
+        };
+        let arg = arg.trim();
+
+        let is_positional = arg.is_empty() || arg.parse::<usize>().is_ok();
+        if has_positional && is_positional {
+            ret.uses_positional = true;
+            let format_trait = get_format_trait(spec);
+            if !ret.positional_traits.contains(&format_trait) {
+                ret.positional_traits.push(format_trait);
+            }
+            ret.value.push('{');
+            ret.value.push_str(&placeholder);
+            ret.value.push('}');
+            continue;
+        }
+
+        if arg.is_empty() {
+            return Err(syn::Error::new_spanned(
+                lit,
+                format!(
+                    "format string `{}` must name a field in every `{{}}`, like `{{name}}`",
+                    source
+                ),
+            ));
+        }
+
+        let name = arg.strip_prefix("self.").unwrap_or(arg);
+        let idx = fields
+            .iter()
+            .enumerate()
+            .position(|(idx, field)| match field.ident {
+                Some(ref ident) => ident == name,
+                None => idx.to_string() == name,
+            })
+            .ok_or_else(|| {
+                syn::Error::new_spanned(
+                    lit,
+                    format!("unknown field `{}` in format string `{}`", name, source),
+                )
+            })?;
+        if !ret.fields.contains(&idx) {
+            ret.fields.push(idx);
+        }
+        let format_trait = (idx, get_format_trait(spec));
+        if !ret.traits.contains(&format_trait) {
+            ret.traits.push(format_trait);
+        }
+        ret.value.push_str(&format!("{{__self_{}{}}}", idx, spec));
     }
 
-    None
+    Ok(ret)
+}
+
+// 格式说明对应的格式化trait，spec是包括`:`在内的部分，如`:?`、`:#x`
+fn get_format_trait(spec: &str) -> &'static str {
+    match spec.trim_end().chars().last() {
+        Some('?') => "Debug",
+        Some('x') => "LowerHex",
+        Some('X') => "UpperHex",
+        Some('o') => "Octal",
+        Some('b') => "Binary",
+        Some('e') => "LowerExp",
+        Some('E') => "UpperExp",
+        Some('p') => "Pointer",
+        _ => "Display",
+    }
+}
+
+// 用格式化字符串输出整个值，如#[debug(fmt = "...")]和#[display("...")]
+fn generate_format_template(
+    fields: &syn::Fields,
+    fmt_lit: &syn::LitStr,
+) -> syn::Result<(proc_macro2::TokenStream, proc_macro2::TokenStream)> {
+    let format = parse_format_string(fmt_lit, fields, false)?;
+    let format_args = generate_format_args(&format, None);
+
+    Ok((
+        generate_fields_pattern(fields),
+        quote::quote!(fmt.write_fmt(#format_args)),
+    ))
+}
+
+// #[debug(transparent)]：整个输出委托给唯一一个没有被跳过的字段
+fn generate_transparent_debug(
+    fields: &syn::Fields,
+    transparent: &syn::Path,
+) -> syn::Result<(proc_macro2::TokenStream, proc_macro2::TokenStream)> {
+    let mut debug_stream = None;
+    for (idx, field) in fields.iter().enumerate() {
+        let options = get_field_options(field)?;
+        if options.skip {
+            continue;
+        }
+        if debug_stream.is_some() {
+            return Err(syn::Error::new_spanned(
+                transparent,
+                "`debug(transparent)` requires exactly one field that is not `debug(skip)`",
+            ));
+        }
+        if let Some(ref skip_if) = options.skip_if {
+            return Err(syn::Error::new_spanned(
+                skip_if,
+                "`debug(skip_if = \"...\")` cannot be used with `debug(transparent)`",
+            ));
+        }
+
+        let binding = quote::format_ident!("__self_{}", idx);
+        // 没有自定义输出方式时直接转发，保留{:.2?}、{:#?}等格式化选项
+        debug_stream =
+            if options.format.is_none() && options.limit.is_none() && options.uses_debug() {
+                Some(quote::quote!(::core::fmt::Debug::fmt(#binding, fmt)))
+            } else {
+                let value = generate_field_value(&binding, &field.ty, &options, fields)?;
+                Some(quote::quote!(::core::fmt::Debug::fmt(&#value, fmt)))
+            };
+    }
+
+    let debug_stream = match debug_stream {
+        Some(debug_stream) => debug_stream,
+        None => {
+            return Err(syn::Error::new_spanned(
+                transparent,
+                "`debug(transparent)` requires exactly one field that is not `debug(skip)`",
+            ))
+        }
+    };
+
+    Ok((generate_fields_pattern(fields), debug_stream))
+}
+
+// 字段上#[debug...]属性中的选项
+#[derive(Default)]
+struct FieldOptions {
+    // #[debug = "..."]或#[debug("...")]，输出该字段时使用的格式化字符串
+    format: Option<syn::LitStr>,
+    // #[debug(skip)]，不输出该字段
+    skip: bool,
+    // #[debug(skip_if = "path")]，运行时path(&field)返回true时不输出该字段
+    skip_if: Option<syn::Path>,
+    // #[debug(redact)]，用占位符代替字段的值，字段类型不需要实现Debug
+    redact: Option<Redact>,
+    // #[debug(with = "path")]，调用fn(&T, &mut Formatter) -> fmt::Result输出该字段
+    with: Option<syn::Path>,
+    // #[debug(bound = "...")]，代替根据该字段类型推断出的约束
+    bound: Option<Vec<syn::WherePredicate>>,
+    // #[debug(rename = "...")]，输出时使用的字段名
+    rename: Option<syn::LitStr>,
+    // #[debug(limit = N)]，集合只输出前N个元素，字节缓冲区只输出前N个字节
+    limit: Option<usize>,
+    // #[debug(hex)]或#[debug(ascii)]，把AsRef<[u8]>的字段输出成一行
+    bytes: Option<Bytes>,
+    // #[debug(ptr)]，用fmt::Pointer输出Rc、Arc等指向的地址，不再递归输出
+    ptr: Option<Ptr>,
+}
+
+impl FieldOptions {
+    // 该字段的值是否会通过Debug输出
+    fn uses_debug(&self) -> bool {
+        !self.skip
+            && self.redact.is_none()
+            && self.with.is_none()
+            && self.bytes.is_none()
+            && self.ptr.is_none()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Ptr {
+    // 字段本身实现了fmt::Pointer
+    Pointer,
+    // Option<P>，P实现了fmt::Pointer，输出Some(0x...)或None
+    Option,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Bytes {
+    // 十六进制，如"0a1bff"
+    Hex,
+    // 字节串字面量的转义形式，如b"GET /\r\n"
+    Ascii,
+}
+
+enum Redact {
+    // #[debug(redact)]输出"<redacted>"，#[debug(redact = "...")]输出指定的字符串
+    Placeholder(String),
+    // #[debug(redact(len))]只输出长度，如"<redacted 32 bytes>"
+    Len,
+}
+
+fn get_field_options(field: &syn::Field) -> syn::Result<FieldOptions> {
+    let mut options = FieldOptions::default();
+    let mut seen = Vec::new();
+
+    // 其他属性（如文档注释）一律忽略，只解析#[debug(...)]和#[debug = "..."]
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("debug"))
+    {
+        match attr.parse_meta()? {
+            // 旧的#[debug = "..."]写法，等同于#[debug("...")]
+            syn::Meta::NameValue(syn::MetaNameValue {
+                lit: syn::Lit::Str(ref lit),
+                ..
+            }) => {
+                check_duplicate_option(
+                    &mut seen,
+                    &syn::NestedMeta::Lit(syn::Lit::Str(lit.clone())),
+                )?;
+                options.format = Some(lit.clone());
+            }
+            syn::Meta::List(syn::MetaList { ref nested, .. }) => {
+                for meta in nested.iter() {
+                    check_duplicate_option(&mut seen, meta)?;
+                    match meta {
+                        syn::NestedMeta::Lit(syn::Lit::Str(lit)) => {
+                            options.format = Some(lit.clone());
+                        }
+                        syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("skip") => {
+                            options.skip = true;
+                        }
+                        syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                            ref path,
+                            lit: syn::Lit::Str(ref lit),
+                            ..
+                        })) if path.is_ident("skip_if") => {
+                            options.skip_if = Some(lit.parse()?);
+                        }
+                        syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                            ref path,
+                            lit: syn::Lit::Str(ref lit),
+                            ..
+                        })) if path.is_ident("with") => {
+                            options.with = Some(lit.parse()?);
+                        }
+                        syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                            ref path,
+                            lit: syn::Lit::Str(ref lit),
+                            ..
+                        })) if path.is_ident("rename") => {
+                            options.rename = Some(lit.clone());
+                        }
+                        syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                            ref path,
+                            lit: syn::Lit::Str(ref lit),
+                            ..
+                        })) if path.is_ident("bound") => {
+                            options
+                                .bound
+                                .get_or_insert_with(Vec::new)
+                                .extend(parse_bound_predicates(lit)?);
+                        }
+                        syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                            ref path,
+                            lit: syn::Lit::Int(ref lit),
+                            ..
+                        })) if path.is_ident("limit") => {
+                            options.limit = Some(lit.base10_parse()?);
+                        }
+                        syn::NestedMeta::Meta(syn::Meta::Path(path))
+                            if path.is_ident("hex") || path.is_ident("ascii") =>
+                        {
+                            if options.bytes.is_some() {
+                                return Err(syn::Error::new_spanned(
+                                    meta,
+                                    "`debug(hex)` and `debug(ascii)` cannot be used together",
+                                ));
+                            }
+                            options.bytes = Some(if path.is_ident("hex") {
+                                Bytes::Hex
+                            } else {
+                                Bytes::Ascii
+                            });
+                        }
+                        syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("ptr") => {
+                            options.ptr = Some(if is_option_type(&field.ty) {
+                                Ptr::Option
+                            } else {
+                                Ptr::Pointer
+                            });
+                        }
+                        syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("redact") => {
+                            options.redact = Some(Redact::Placeholder("<redacted>".to_string()));
+                        }
+                        syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                            ref path,
+                            lit: syn::Lit::Str(ref lit),
+                            ..
+                        })) if path.is_ident("redact") => {
+                            options.redact = Some(Redact::Placeholder(lit.value()));
+                        }
+                        syn::NestedMeta::Meta(syn::Meta::List(syn::MetaList {
+                            ref path,
+                            ref nested,
+                            ..
+                        })) if path.is_ident("redact") => match nested.first() {
+                            Some(syn::NestedMeta::Meta(syn::Meta::Path(arg)))
+                                if nested.len() == 1 && arg.is_ident("len") =>
+                            {
+                                options.redact = Some(Redact::Len);
+                            }
+                            _ => {
+                                return Err(syn::Error::new_spanned(
+                                    meta,
+                                    "expected `debug(redact(len))`",
+                                ))
+                            }
+                        },
+                        _ => return Err(invalid_option_error(meta, true)),
+                    }
+                }
+            }
+            meta => {
+                return Err(syn::Error::new_spanned(
+                    meta,
+                    r#"expected `#[debug(...)]` or `#[debug = "..."]`"#,
+                ))
+            }
+        }
+    }
+
+    // limit、hex、ascii只作用于默认的输出方式
+    if (options.limit.is_some() || options.bytes.is_some())
+        && (options.format.is_some() || options.redact.is_some() || options.with.is_some())
+    {
+        return Err(syn::Error::new_spanned(
+            field,
+            r#"`debug(limit = N)`, `debug(hex)` and `debug(ascii)` cannot be combined with a format string, `debug(redact)` or `debug(with = "...")`"#,
+        ));
+    }
+    if options.ptr.is_some()
+        && (options.format.is_some()
+            || options.redact.is_some()
+            || options.with.is_some()
+            || options.limit.is_some()
+            || options.bytes.is_some())
+    {
+        return Err(syn::Error::new_spanned(
+            field,
+            "`debug(ptr)` cannot be combined with any other way of printing the field",
+        ));
+    }
+
+    Ok(options)
+}
+
+// 遍历字段类型，记录每个泛型参数出现的位置，据此推断最小的Debug约束
+struct TypePathVisit {
+    generic_type_names: Vec<syn::Ident>,
+    // 直接以`T`的形式出现，需要`T: Debug`
+    bare_type_params: Vec<syn::Ident>,
+    // 以`T::Value`或`<T as Trait>::Value`的形式出现，需要`T::Value: Debug`
+    associated_types: Vec<syn::TypePath>,
+    // 直接约束整个字段类型的字段，如`Sender<T>: Debug`，不要求`T: Debug`
+    field_types: Vec<syn::Type>,
+    // 字段类型中出现了标准库以外的泛型类型，如`Two<T>`
+    uses_foreign_generic_type: bool,
+}
+
+// 标准库中常用的泛型类型，它们的Debug实现不会反过来依赖派生出的实现
+const STD_GENERIC_TYPES: &[&str] = &[
+    "Option",
+    "Result",
+    "Box",
+    "Vec",
+    "VecDeque",
+    "LinkedList",
+    "BinaryHeap",
+    "HashMap",
+    "HashSet",
+    "BTreeMap",
+    "BTreeSet",
+    "Rc",
+    "Arc",
+    "Weak",
+    "Cell",
+    "RefCell",
+    "Mutex",
+    "RwLock",
+    "Cow",
+    "Pin",
+    "NonNull",
+    "ManuallyDrop",
+    "Wrapping",
+    "Reverse",
+    "Sender",
+    "SyncSender",
+    "Receiver",
+    "JoinHandle",
+];
+
+// std::...、core::...、alloc::...路径，或者上面列出的标准库类型
+fn is_std_generic_type(path: &syn::Path) -> bool {
+    let first = &path.segments.first().unwrap().ident;
+    (path.segments.len() > 1 && (first == "std" || first == "core" || first == "alloc"))
+        || STD_GENERIC_TYPES
+            .iter()
+            .any(|name| path.segments.last().unwrap().ident == name)
+}
+
+impl TypePathVisit {
+    fn is_generic_type_param(&self, ident: &syn::Ident) -> bool {
+        self.generic_type_names.contains(ident)
+    }
+
+    fn mentions_generic_type_param(&self, ty: &syn::Type) -> bool {
+        let mut visitor = TypePathVisit::new(self.generic_type_names.clone());
+        visitor.visit_type(ty);
+        visitor.mentions_any_type_param()
+    }
+
+    fn new(generic_type_names: Vec<syn::Ident>) -> Self {
+        TypePathVisit {
+            generic_type_names,
+            bare_type_params: Vec::new(),
+            associated_types: Vec::new(),
+            field_types: Vec::new(),
+            uses_foreign_generic_type: false,
+        }
+    }
+
+    fn mentions_any_type_param(&self) -> bool {
+        !self.bare_type_params.is_empty() || !self.associated_types.is_empty()
+    }
+
+    fn push_bare_type_param(&mut self, ident: &syn::Ident) {
+        if !self.bare_type_params.contains(ident) {
+            self.bare_type_params.push(ident.clone());
+        }
+    }
+
+    fn push_associated_type(&mut self, node: &syn::TypePath) {
+        if !self.associated_types.contains(node) {
+            self.associated_types.push(node.clone());
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for TypePathVisit {
+    fn visit_type_path(&mut self, node: &'ast syn::TypePath) {
+        // <T as Trait>::Value
+        if let Some(ref qself) = node.qself {
+            if self.mentions_generic_type_param(&qself.ty) {
+                self.push_associated_type(node);
+            }
+            return;
+        }
+
+        let segments = &node.path.segments;
+        if node.path.leading_colon.is_none() && self.is_generic_type_param(&segments[0].ident) {
+            if segments.len() == 1 {
+                // T
+                self.push_bare_type_param(&segments[0].ident);
+            } else {
+                // T::Value
+                self.push_associated_type(node);
+            }
+            return;
+        }
+
+        // PhantomData<T>不论T是什么都实现了Debug，包括std::marker::PhantomData<T>这样的写法
+        if segments.last().unwrap().ident == "PhantomData" {
+            return;
+        }
+
+        let has_generic_args = segments.iter().any(|seg| !seg.arguments.is_empty());
+        if has_generic_args && !is_std_generic_type(&node.path) {
+            self.uses_foreign_generic_type = true;
+        }
+
+        // Visit 模式要求在当前节点访问完成后，继续调用默认实现的visit方法，从而遍历到所有的
+        // 必须调用这个函数，否则遍历到这个节点就不再往更深层走了
+        visit::visit_type_path(self, node)
+    }
+
+    // 函数指针、裸指针不论参数是什么都实现了Debug
+    fn visit_type_bare_fn(&mut self, _: &'ast syn::TypeBareFn) {}
+
+    fn visit_type_ptr(&mut self, _: &'ast syn::TypePtr) {}
+
+    // trait对象和impl Trait是否实现Debug与泛型参数无关，无法推断
+    fn visit_type_trait_object(&mut self, _: &'ast syn::TypeTraitObject) {}
+
+    fn visit_type_impl_trait(&mut self, _: &'ast syn::TypeImplTrait) {}
+
+    fn visit_type_macro(&mut self, _: &'ast syn::TypeMacro) {}
+
+    // [T; N]中的长度表达式不是类型，const泛型参数不需要约束
+    fn visit_expr(&mut self, _: &'ast syn::Expr) {}
+}
+
+// 用到泛型参数的字段，只由标准库类型组成时直接约束整个字段类型，如`Sender<T>: Debug`
+// 其中出现了其他泛型类型时，像`Option<Box<Two<T>>>: Debug`这样的约束可能与Two<T>的实现
+// 互相依赖形成循环，所以退回到约束泛型参数本身，如`T: Debug`
+// by_field_type为false时总是约束泛型参数本身
+fn get_type_param_usage(
+    st: &syn::DeriveInput,
+    fields: &[&Field],
+    by_field_type: bool,
+) -> TypePathVisit {
+    // 找出泛型，生命周期参数和const泛型参数不需要Debug约束
+    let generic_type_names: Vec<_> = st.generics.type_params().map(|t| t.ident.clone()).collect();
+    let mut usage = TypePathVisit::new(generic_type_names.clone());
+
+    // 根据泛型去找字段类型中用到泛型的位置
+    for field in fields {
+        let mut visitor = TypePathVisit::new(generic_type_names.clone());
+        visitor.visit_type(&field.ty);
+        if !visitor.mentions_any_type_param() {
+            continue;
+        }
+        if by_field_type && !visitor.uses_foreign_generic_type {
+            if !usage.field_types.contains(&field.ty) {
+                usage.field_types.push(field.ty.clone());
+            }
+            continue;
+        }
+        for ident in visitor.bare_type_params.iter() {
+            usage.push_bare_type_param(ident);
+        }
+        for associated_type in visitor.associated_types.iter() {
+            usage.push_associated_type(associated_type);
+        }
+    }
+
+    usage
+}
+
+// 结构体或枚举上#[debug(...)]属性中的选项
+#[derive(Default)]
+struct StructOptions {
+    // 所有#[debug(bound = "...")]中的约束，存在时不再自动推断任何约束
+    bound: Option<Vec<syn::WherePredicate>>,
+    // #[debug(name = "...")]，输出时使用的结构体名
+    name: Option<syn::LitStr>,
+    non_exhaustive: NonExhaustive,
+    // #[debug(transparent)]，直接输出唯一的字段
+    transparent: Option<syn::Path>,
+    // #[debug(fmt = "...")]，用引用字段的格式化字符串代替整个输出
+    fmt: Option<syn::LitStr>,
+    // #[debug(reflect)]，额外生成debug_fields()逐个返回输出的字段
+    reflect: bool,
+    // #[debug(opaque)]，不输出字段，只输出类型名和大小
+    opaque: Option<Opaque>,
+    // #[debug(max_depth = N)]，嵌套输出超过N层后只输出`...`
+    max_depth: Option<usize>,
+    // #[debug(entry(name = "...", fmt = "..."))]，在所有字段之后额外输出的条目
+    entries: Vec<Entry>,
+}
+
+// 不对应任何字段的条目，值由引用字段的格式化字符串生成
+struct Entry {
+    name: syn::LitStr,
+    fmt: syn::LitStr,
+}
+
+#[derive(Clone, Copy)]
+enum Opaque {
+    // #[debug(opaque)]
+    Size,
+    // #[debug(opaque(hex))]，额外以十六进制输出值的所有字节
+    Hex,
+}
+
+#[derive(Clone, Copy, PartialEq, Default)]
+enum NonExhaustive {
+    // 默认不输出`..`
+    #[default]
+    Never,
+    // #[debug(non_exhaustive)]，有字段被跳过时以`..`结束
+    WhenSkipped,
+    // #[debug(non_exhaustive(always))]，总是以`..`结束
+    Always,
+}
+
+fn get_struct_options(st: &syn::DeriveInput) -> syn::Result<StructOptions> {
+    let mut options = StructOptions::default();
+    let mut seen = Vec::new();
+
+    for attr in st.attrs.iter().filter(|attr| attr.path.is_ident("debug")) {
+        match attr.parse_meta()? {
+            syn::Meta::List(syn::MetaList { ref nested, .. }) => {
+                for meta in nested.iter() {
+                    check_duplicate_option(&mut seen, meta)?;
+                    match meta {
+                        syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                            ref path,
+                            lit: syn::Lit::Str(ref lit),
+                            ..
+                        })) if path.is_ident("bound") => {
+                            options
+                                .bound
+                                .get_or_insert_with(Vec::new)
+                                .extend(parse_bound_predicates(lit)?);
+                        }
+                        syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                            ref path,
+                            lit: syn::Lit::Str(ref lit),
+                            ..
+                        })) if path.is_ident("name") => {
+                            options.name = Some(lit.clone());
+                        }
+                        syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                            ref path,
+                            lit: syn::Lit::Str(ref lit),
+                            ..
+                        })) if path.is_ident("fmt") => {
+                            options.fmt = Some(lit.clone());
+                        }
+                        syn::NestedMeta::Meta(syn::Meta::Path(path))
+                            if path.is_ident("transparent") =>
+                        {
+                            options.transparent = Some(path.clone());
+                        }
+                        syn::NestedMeta::Meta(syn::Meta::Path(path))
+                            if path.is_ident("reflect") =>
+                        {
+                            options.reflect = true;
+                        }
+                        syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                            ref path,
+                            lit: syn::Lit::Int(ref lit),
+                            ..
+                        })) if path.is_ident("max_depth") => {
+                            options.max_depth = Some(lit.base10_parse()?);
+                        }
+                        syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("opaque") => {
+                            options.opaque = Some(Opaque::Size);
+                        }
+                        syn::NestedMeta::Meta(syn::Meta::List(syn::MetaList {
+                            ref path,
+                            ref nested,
+                            ..
+                        })) if path.is_ident("entry") => {
+                            options.entries.push(get_entry(meta, nested)?);
+                        }
+                        syn::NestedMeta::Meta(syn::Meta::List(syn::MetaList {
+                            ref path,
+                            ref nested,
+                            ..
+                        })) if path.is_ident("opaque") => match nested.first() {
+                            Some(syn::NestedMeta::Meta(syn::Meta::Path(arg)))
+                                if nested.len() == 1 && arg.is_ident("hex") =>
+                            {
+                                options.opaque = Some(Opaque::Hex);
+                            }
+                            _ => {
+                                return Err(syn::Error::new_spanned(
+                                    meta,
+                                    "expected `debug(opaque(hex))`",
+                                ))
+                            }
+                        },
+                        syn::NestedMeta::Meta(syn::Meta::Path(path))
+                            if path.is_ident("non_exhaustive") =>
+                        {
+                            options.non_exhaustive = NonExhaustive::WhenSkipped;
+                        }
+                        syn::NestedMeta::Meta(syn::Meta::List(syn::MetaList {
+                            ref path,
+                            ref nested,
+                            ..
+                        })) if path.is_ident("non_exhaustive") => match nested.first() {
+                            Some(syn::NestedMeta::Meta(syn::Meta::Path(arg)))
+                                if nested.len() == 1 && arg.is_ident("always") =>
+                            {
+                                options.non_exhaustive = NonExhaustive::Always;
+                            }
+                            _ => {
+                                return Err(syn::Error::new_spanned(
+                                    meta,
+                                    "expected `debug(non_exhaustive(always))`",
+                                ))
+                            }
+                        },
+                        _ => return Err(invalid_option_error(meta, false)),
+                    }
+                }
+            }
+            syn::Meta::NameValue(meta) => {
+                return Err(syn::Error::new_spanned(
+                    meta,
+                    r#"`#[debug = "..."]` can only be used on a field, use `#[debug(fmt = "...")]` instead"#,
+                ))
+            }
+            syn::Meta::Path(path) => {
+                return Err(syn::Error::new_spanned(path, "expected `#[debug(...)]`"))
+            }
+        }
+    }
+
+    Ok(options)
+}
+
+// entry(name = "...", fmt = "...")，两项都必须给出
+fn get_entry(
+    meta: &syn::NestedMeta,
+    nested: &Punctuated<syn::NestedMeta, Token![,]>,
+) -> syn::Result<Entry> {
+    let mut name = None;
+    let mut fmt = None;
+    for arg in nested.iter() {
+        match arg {
+            syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                ref path,
+                lit: syn::Lit::Str(ref lit),
+                ..
+            })) if path.is_ident("name") && name.is_none() => name = Some(lit.clone()),
+            syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                ref path,
+                lit: syn::Lit::Str(ref lit),
+                ..
+            })) if path.is_ident("fmt") && fmt.is_none() => fmt = Some(lit.clone()),
+            _ => break,
+        }
+    }
+
+    match (name, fmt) {
+        (Some(name), Some(fmt)) if nested.len() == 2 => Ok(Entry { name, fmt }),
+        _ => Err(syn::Error::new_spanned(
+            meta,
+            r#"expected `debug(entry(name = "...", fmt = "..."))`"#,
+        )),
+    }
+}
+
+// 变体上只支持#[debug(name = "...")]，替换输出中变体的名字
+fn get_variant_name(variant: &syn::Variant) -> syn::Result<Option<syn::LitStr>> {
+    let mut name = None;
+    let mut seen = Vec::new();
+
+    for attr in variant
+        .attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("debug"))
+    {
+        let nested = match attr.parse_meta()? {
+            syn::Meta::List(syn::MetaList { nested, .. }) => nested,
+            meta => {
+                return Err(syn::Error::new_spanned(
+                    meta,
+                    r#"expected `#[debug(name = "...")]` on a variant"#,
+                ))
+            }
+        };
+        for meta in nested.iter() {
+            check_duplicate_option(&mut seen, meta)?;
+            match meta {
+                syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                    ref path,
+                    lit: syn::Lit::Str(ref lit),
+                    ..
+                })) if path.is_ident("name") => name = Some(lit.clone()),
+                _ => return Err(invalid_variant_option_error(meta)),
+            }
+        }
+    }
+
+    Ok(name)
+}
+
+// 变体上选项写法不对时的错误，和invalid_option_error一样，未知选项指向选项名并给出建议
+fn invalid_variant_option_error(meta: &syn::NestedMeta) -> syn::Error {
+    let name = match get_option_name(meta) {
+        Some(name) => name,
+        None => return syn::Error::new_spanned(meta, "expected a `debug` option"),
+    };
+    if name == "name" {
+        return syn::Error::new_spanned(meta, r#"expected `debug(name = "...")`"#);
+    }
+    if FIELD_OPTIONS
+        .iter()
+        .chain(STRUCT_OPTIONS.iter())
+        .any(|(option, _)| *option == name)
+    {
+        return syn::Error::new_spanned(
+            meta,
+            format!(
+                r#"`debug({})` is not supported on a variant, only `debug(name = "...")` is"#,
+                name
+            ),
+        );
+    }
+
+    let path = match meta {
+        syn::NestedMeta::Meta(meta) => meta.path(),
+        syn::NestedMeta::Lit(_) => unreachable!(),
+    };
+    let message = if edit_distance(&name, "name") <= 2 {
+        format!("unknown `debug` option `{}`, did you mean `name`?", name)
+    } else {
+        format!(
+            r#"unknown `debug` option `{}`, only `debug(name = "...")` is supported on a variant"#,
+            name
+        )
+    };
+    syn::Error::new_spanned(path, message)
+}
+
+// #[debug(...)]中每个选项的名字及其所有合法写法，字段上的格式化字符串记作`"..."`
+type OptionTable = &'static [(&'static str, &'static [&'static str])];
+
+const FIELD_OPTIONS: OptionTable = &[
+    (r#""...""#, &[r#"debug("...")"#]),
+    ("skip", &["debug(skip)"]),
+    ("skip_if", &[r#"debug(skip_if = "...")"#]),
+    (
+        "redact",
+        &[
+            "debug(redact)",
+            r#"debug(redact = "...")"#,
+            "debug(redact(len))",
+        ],
+    ),
+    ("with", &[r#"debug(with = "...")"#]),
+    ("bound", &[r#"debug(bound = "...")"#]),
+    ("rename", &[r#"debug(rename = "...")"#]),
+    ("limit", &["debug(limit = N)"]),
+    ("hex", &["debug(hex)"]),
+    ("ascii", &["debug(ascii)"]),
+    ("ptr", &["debug(ptr)"]),
+];
+
+const STRUCT_OPTIONS: OptionTable = &[
+    ("bound", &[r#"debug(bound = "...")"#]),
+    ("name", &[r#"debug(name = "...")"#]),
+    ("fmt", &[r#"debug(fmt = "...")"#]),
+    (
+        "non_exhaustive",
+        &["debug(non_exhaustive)", "debug(non_exhaustive(always))"],
+    ),
+    ("transparent", &["debug(transparent)"]),
+    ("reflect", &["debug(reflect)"]),
+    ("opaque", &["debug(opaque)", "debug(opaque(hex))"]),
+    ("max_depth", &["debug(max_depth = N)"]),
+    ("entry", &[r#"debug(entry(name = "...", fmt = "..."))"#]),
+];
+
+// 选项的名字，格式化字符串记作`"..."`，无法识别的写法返回None
+fn get_option_name(meta: &syn::NestedMeta) -> Option<String> {
+    match meta {
+        syn::NestedMeta::Lit(syn::Lit::Str(_)) => Some(r#""...""#.to_string()),
+        syn::NestedMeta::Lit(_) => None,
+        syn::NestedMeta::Meta(meta) => meta.path().get_ident().map(|ident| ident.to_string()),
+    }
+}
+
+// 除bound和entry外每个选项只能出现一次，包括分散在多个#[debug(...)]中的情况
+fn check_duplicate_option(seen: &mut Vec<String>, meta: &syn::NestedMeta) -> syn::Result<()> {
+    let name = match get_option_name(meta) {
+        Some(name) if name != "bound" && name != "entry" => name,
+        _ => return Ok(()),
+    };
+    if seen.contains(&name) {
+        let message = if name.starts_with('"') {
+            "duplicate format string".to_string()
+        } else {
+            format!("duplicate `debug({})` option", name)
+        };
+        return Err(syn::Error::new_spanned(meta, message));
+    }
+    seen.push(name);
+
+    Ok(())
+}
+
+// 选项写法不对时的错误：已知选项给出合法写法，用错位置的选项指出该用在哪里，
+// 未知选项指向选项名并给出拼写最接近的建议
+fn invalid_option_error(meta: &syn::NestedMeta, on_field: bool) -> syn::Error {
+    let (options, other_options) = if on_field {
+        (FIELD_OPTIONS, STRUCT_OPTIONS)
+    } else {
+        (STRUCT_OPTIONS, FIELD_OPTIONS)
+    };
+    let name = match get_option_name(meta) {
+        Some(name) => name,
+        None => return syn::Error::new_spanned(meta, "expected a `debug` option"),
+    };
+
+    if let Some((_, forms)) = options.iter().find(|(option, _)| *option == name) {
+        let forms: Vec<_> = forms.iter().map(|form| format!("`{}`", form)).collect();
+        return syn::Error::new_spanned(meta, format!("expected {}", forms.join(" or ")));
+    }
+    if other_options.iter().any(|(option, _)| *option == name) {
+        let message = if name.starts_with('"') {
+            r#"a format string can only be used on a field, use `debug(fmt = "...")` instead"#
+                .to_string()
+        } else if on_field {
+            format!(
+                "`debug({})` can only be used on a struct or enum, not on a field",
+                name
+            )
+        } else {
+            format!("`debug({})` can only be used on a field", name)
+        };
+        return syn::Error::new_spanned(meta, message);
+    }
+
+    let path = match meta {
+        syn::NestedMeta::Meta(meta) => meta.path(),
+        syn::NestedMeta::Lit(_) => unreachable!(),
+    };
+    let suggestion = options
+        .iter()
+        .filter(|(option, _)| !option.starts_with('"'))
+        .map(|(option, _)| (edit_distance(&name, option), *option))
+        .filter(|&(distance, option)| distance <= 2 && distance < option.len())
+        .min();
+    let message = match suggestion {
+        Some((_, option)) => format!(
+            "unknown `debug` option `{}`, did you mean `{}`?",
+            name, option
+        ),
+        None => {
+            let names: Vec<_> = options
+                .iter()
+                .map(|(option, _)| format!("`{}`", option))
+                .collect();
+            format!(
+                "unknown `debug` option `{}`, expected one of {}",
+                name,
+                names.join(", ")
+            )
+        }
+    };
+
+    syn::Error::new_spanned(path, message)
+}
+
+// 两个字符串之间的编辑距离，用于给拼错的选项名提供建议
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == cb {
+                previous
+            } else {
+                1 + previous.min(row[j]).min(current)
+            };
+            previous = current;
+        }
+    }
+
+    row[b.len()]
+}
+
+// 解析bound = "..."中以逗号分隔的where谓词，空字符串表示没有约束
+fn parse_bound_predicates(lit: &syn::LitStr) -> syn::Result<Vec<syn::WherePredicate>> {
+    let predicates = lit
+        .parse_with(Punctuated::<syn::WherePredicate, Token![,]>::parse_terminated)
+        .map_err(|err| {
+            syn::Error::new_spanned(lit, format!("invalid bound `{}`: {}", lit.value(), err))
+        })?;
+
+    Ok(predicates.into_iter().collect())
+}
+
+fn generate_display_trait(st: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let struct_name_ident = &st.ident;
+    let mut generics = st.generics.clone();
+    let (template, bound) = get_display_options(&st.attrs)?;
+
+    // 每个结构体或变体各自的格式化字符串，以及其中引用到的字段
+    let mut templates = Vec::new();
+    let fmt_body_stream = match st.data {
+        syn::Data::Struct(syn::DataStruct { ref fields, .. }) => {
+            let template = template.ok_or_else(|| {
+                syn::Error::new_spanned(st, r#"missing `#[display("...")]` on the struct"#)
+            })?;
+            let (pattern, display_stream) = generate_format_template(fields, &template)?;
+            templates.push((fields, template));
+            quote::quote! {
+                match self {
+                    #struct_name_ident #pattern => #display_stream,
+                }
+            }
+        }
+        syn::Data::Enum(syn::DataEnum { ref variants, .. }) => {
+            if let Some(template) = template {
+                return Err(syn::Error::new_spanned(
+                    template,
+                    r#"put `#[display("...")]` on each variant of an enum"#,
+                ));
+            }
+            let mut arms = Vec::new();
+            for variant in variants.iter() {
+                let variant_ident = &variant.ident;
+                let (template, variant_bound) = get_display_options(&variant.attrs)?;
+                let template = template.ok_or_else(|| {
+                    syn::Error::new_spanned(
+                        variant,
+                        r#"missing `#[display("...")]` on the variant"#,
+                    )
+                })?;
+                if variant_bound.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        variant,
+                        r#"`display(bound = "...")` is only supported on the enum itself"#,
+                    ));
+                }
+                let (pattern, display_stream) =
+                    generate_format_template(&variant.fields, &template)?;
+                templates.push((&variant.fields, template));
+                arms.push(quote::quote! {
+                    Self::#variant_ident #pattern => #display_stream,
+                });
+            }
+            if arms.is_empty() {
+                quote::quote!(match *self {})
+            } else {
+                quote::quote! {
+                    match self {
+                        #(#arms)*
+                    }
+                }
+            }
+        }
+        syn::Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                st,
+                "Must define on a Struct or Enum, not Union".to_string(),
+            ))
+        }
+    };
+
+    if let Some(hatch) = bound {
+        generics.make_where_clause().predicates.extend(hatch);
+    } else {
+        let mut formatted_fields = Vec::new();
+        for (fields, template) in templates.iter() {
+            for (idx, format_trait) in parse_format_string(template, fields, false)?.traits {
+                formatted_fields.push((format_trait, fields.iter().nth(idx).unwrap()));
+            }
+        }
+        add_format_trait_bounds(st, &mut generics, &formatted_fields);
+    }
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let ret_stream = quote::quote! {
+        impl #impl_generics ::core::fmt::Display for #struct_name_ident #ty_generics #where_clause{
+            fn fmt(&self,fmt:&mut ::core::fmt::Formatter)->::core::fmt::Result{
+                #fmt_body_stream
+            }
+        }
+    };
+
+    Ok(ret_stream)
+}
+
+// 结构体、枚举或变体上的#[display("...", bound = "...")]
+fn get_display_options(
+    attrs: &[syn::Attribute],
+) -> syn::Result<(Option<syn::LitStr>, Option<Vec<syn::WherePredicate>>)> {
+    let mut template = None;
+    let mut bound: Option<Vec<syn::WherePredicate>> = None;
+
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("display")) {
+        if let syn::Meta::List(syn::MetaList { ref nested, .. }) = attr.parse_meta()? {
+            for meta in nested.iter() {
+                match meta {
+                    syn::NestedMeta::Lit(syn::Lit::Str(lit)) => {
+                        template = Some(lit.clone());
+                    }
+                    syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                        ref path,
+                        lit: syn::Lit::Str(ref lit),
+                        ..
+                    })) if path.is_ident("bound") => {
+                        bound
+                            .get_or_insert_with(Vec::new)
+                            .extend(parse_bound_predicates(lit)?);
+                    }
+                    _ => {
+                        return Err(syn::Error::new_spanned(
+                            meta,
+                            r#"expected one of `display("...")`, `display(bound = "...")`"#,
+                        ))
+                    }
+                }
+            }
+        } else {
+            return Err(syn::Error::new_spanned(
+                attr,
+                r#"expected `display("...")`"#,
+            ));
+        }
+    }
+
+    Ok((template, bound))
+}
+
+// 结构化日志中字段值的写法，由字段类型在语法上判断，判断不了的都按Debug输出成字符串
+enum LogKind {
+    // 字符串，用Display输出后转义，两边加引号
+    Str,
+    // 整数和布尔值，直接用Display输出
+    Raw,
+    // 浮点数，JSON中NaN和无穷大写成null
+    Float,
+    // 其他类型，以及自定义了输出方式的字段，把Debug的输出当作字符串
+    Debug,
+    // Option<T>，JSON中None写成null，logfmt中None的字段整个省略
+    Option(Box<LogKind>),
+    // Vec<T>、[T; N]和&[T]，写成JSON数组，logfmt中把数组写成带引号的字符串
+    List(Box<LogKind>),
+}
+
+impl LogKind {
+    fn uses_escape(&self) -> bool {
+        match self {
+            LogKind::Raw | LogKind::Float => false,
+            LogKind::Option(inner) => inner.uses_escape(),
+            LogKind::Str | LogKind::Debug | LogKind::List(_) => true,
+        }
+    }
+}
+
+// Option和列表只在元素类型本身能判断出写法时才展开，元素要按Debug输出时整个字段都按Debug输出，
+// 因为推断出的`Vec<T>: Debug`这样的约束并不能让生成的代码使用`T: Debug`
+fn get_log_kind(ty: &syn::Type) -> LogKind {
+    match ty {
+        syn::Type::Reference(syn::TypeReference { ref elem, .. })
+        | syn::Type::Paren(syn::TypeParen { ref elem, .. })
+        | syn::Type::Group(syn::TypeGroup { ref elem, .. }) => get_log_kind(elem),
+        syn::Type::Array(syn::TypeArray { ref elem, .. })
+        | syn::Type::Slice(syn::TypeSlice { ref elem, .. }) => match get_log_kind(elem) {
+            LogKind::Debug => LogKind::Debug,
+            inner => LogKind::List(Box::new(inner)),
+        },
+        syn::Type::Path(syn::TypePath { qself: None, path }) => {
+            let segment = path.segments.last().unwrap();
+            if let syn::PathArguments::AngleBracketed(ref args) = segment.arguments {
+                let inner = match args.args.first() {
+                    Some(syn::GenericArgument::Type(inner)) if args.args.len() == 1 => {
+                        get_log_kind(inner)
+                    }
+                    _ => return LogKind::Debug,
+                };
+                return match (segment.ident.to_string().as_str(), inner) {
+                    (_, LogKind::Debug) => LogKind::Debug,
+                    ("Option", inner) => LogKind::Option(Box::new(inner)),
+                    ("Vec", inner) => LogKind::List(Box::new(inner)),
+                    _ => LogKind::Debug,
+                };
+            }
+            if !segment.arguments.is_empty() {
+                return LogKind::Debug;
+            }
+            match segment.ident.to_string().as_str() {
+                "str" | "String" | "char" => LogKind::Str,
+                "bool" | "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32"
+                | "i64" | "i128" | "isize" => LogKind::Raw,
+                "f32" | "f64" => LogKind::Float,
+                _ => LogKind::Debug,
+            }
+        }
+        _ => LogKind::Debug,
+    }
+}
+
+// 生成把expr按JSON的值写入w的代码，expr是字段或元素的引用
+fn generate_json_value(
+    kind: &LogKind,
+    expr: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    match kind {
+        LogKind::Str | LogKind::Debug => {
+            let format = if let LogKind::Str = kind {
+                "{}"
+            } else {
+                "{:?}"
+            };
+            quote::quote! {
+                ::core::fmt::Write::write_str(w, "\"")?;
+                ::core::fmt::Write::write_fmt(
+                    &mut __LogEscape(&mut *w),
+                    ::core::format_args!(#format, #expr),
+                )?;
+                ::core::fmt::Write::write_str(w, "\"")?;
+            }
+        }
+        LogKind::Raw => quote::quote! {
+            ::core::fmt::Write::write_fmt(w, ::core::format_args!("{}", #expr))?;
+        },
+        LogKind::Float => quote::quote! {
+            if #expr.is_finite() {
+                ::core::fmt::Write::write_fmt(w, ::core::format_args!("{}", #expr))?;
+            } else {
+                ::core::fmt::Write::write_str(w, "null")?;
+            }
+        },
+        LogKind::Option(inner) => {
+            let inner = generate_json_value(inner, &quote::quote!(__value));
+            quote::quote! {
+                match #expr {
+                    ::core::option::Option::Some(__value) => {
+                        #inner
+                    }
+                    ::core::option::Option::None => ::core::fmt::Write::write_str(w, "null")?,
+                }
+            }
+        }
+        LogKind::List(inner) => {
+            let inner = generate_json_value(inner, &quote::quote!(__item));
+            quote::quote! {
+                ::core::fmt::Write::write_str(w, "[")?;
+                for (__index, __item) in ::core::iter::Iterator::enumerate(#expr.iter()) {
+                    if __index > 0 {
+                        ::core::fmt::Write::write_str(w, ",")?;
+                    }
+                    #inner
+                }
+                ::core::fmt::Write::write_str(w, "]")?;
+            }
+        }
+    }
+}
+
+// 生成写入一个logfmt键值对的代码，值为None的Option不写
+fn generate_logfmt_pair(
+    kind: &LogKind,
+    expr: &proc_macro2::TokenStream,
+    key: &str,
+) -> proc_macro2::TokenStream {
+    let value = match kind {
+        LogKind::Option(inner) => {
+            let pair = generate_logfmt_pair(inner, &quote::quote!(__value), key);
+            return quote::quote! {
+                if let ::core::option::Option::Some(__value) = #expr {
+                    #pair
+                }
+            };
+        }
+        // 数组写成JSON后整体作为带引号的字符串再转义一次
+        LogKind::List(_) => {
+            let json = generate_json_value(kind, expr);
+            quote::quote! {
+                ::core::fmt::Write::write_str(w, "\"")?;
+                {
+                    let w = &mut __LogEscape(&mut *w);
+                    #json
+                }
+                ::core::fmt::Write::write_str(w, "\"")?;
+            }
+        }
+        LogKind::Float => quote::quote! {
+            ::core::fmt::Write::write_fmt(w, ::core::format_args!("{}", #expr))?;
+        },
+        _ => generate_json_value(kind, expr),
+    };
+    quote::quote! {
+        if !__first {
+            ::core::fmt::Write::write_str(w, " ")?;
+        }
+        __first = false;
+        ::core::fmt::Write::write_str(w, #key)?;
+        #value
+    }
+}
+
+// logfmt的键不加引号，不能为空，也不能含有空白、`=`、`"`和控制字符
+fn check_logfmt_key(name: &str, lit: Option<&syn::LitStr>) -> syn::Result<()> {
+    let lit = match lit {
+        Some(lit) => lit,
+        None => return Ok(()),
+    };
+    if name.is_empty() {
+        return Err(syn::Error::new_spanned(lit, "logfmt keys cannot be empty"));
+    }
+    if name
+        .chars()
+        .any(|c| c.is_whitespace() || c.is_control() || c == '=' || c == '"')
+    {
+        return Err(syn::Error::new_spanned(
+            lit,
+            format!(
+                "`{}` is not a valid logfmt key, keys cannot contain whitespace, `=` or `\"`",
+                name
+            ),
+        ));
+    }
+    Ok(())
+}
+
+// 按JSON字符串的规则转义，生成代码中的__LogEscape在运行时做同样的事
+fn escape_log_str(value: &str) -> String {
+    let mut ret = String::new();
+    for c in value.chars() {
+        match c {
+            '"' => ret.push_str("\\\""),
+            '\\' => ret.push_str("\\\\"),
+            '\n' => ret.push_str("\\n"),
+            '\r' => ret.push_str("\\r"),
+            '\t' => ret.push_str("\\t"),
+            c if (c as u32) < 0x20 => ret.push_str(&format!("\\u{:04x}", c as u32)),
+            c => ret.push(c),
+        }
+    }
+    ret
+}
+
+// 生成write_json()和write_logfmt()，字段的取舍、命名和输出方式与CustomDebug一致
+fn generate_log_methods(st: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let struct_name_ident = &st.ident;
+    let fields = match st.data {
+        syn::Data::Struct(syn::DataStruct { ref fields, .. }) => fields,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                st,
+                "CustomLog can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut generics = st.generics.clone();
+    let struct_options = get_struct_options(st)?;
+    // 结构体上的fmt只影响Debug的输出，日志中总是逐个输出字段
+    let printed = get_printed_fields(st, None, &struct_options.entries, true)?;
+    let mut helper_defs = generate_helper_defs(&printed);
+    add_debug_bounds(st, &mut generics, struct_options.bound, printed);
+
+    let mut uses_escape = false;
+    let mut json_stmts = Vec::new();
+    let mut logfmt_stmts = Vec::new();
+    for (idx, field) in fields.iter().enumerate() {
+        let options = get_field_options(field)?;
+        if options.skip {
+            continue;
+        }
+
+        let binding = quote::format_ident!("__self_{}", idx);
+        let name = match (&options.rename, &field.ident) {
+            (Some(rename), _) => rename.value(),
+            (None, Some(ident)) => ident.to_string(),
+            (None, None) => idx.to_string(),
+        };
+        check_logfmt_key(&name, options.rename.as_ref())?;
+        let json_key = format!("\"{}\":", escape_log_str(&name));
+        let logfmt_key = format!("{}=", name);
+
+        let (kind, expr) =
+            if options.format.is_none() && options.limit.is_none() && options.uses_debug() {
+                (get_log_kind(&field.ty), quote::quote!(#binding))
+            } else {
+                (
+                    LogKind::Debug,
+                    generate_field_value(&binding, &field.ty, &options, fields)?,
+                )
+            };
+        uses_escape |= kind.uses_escape();
+        let json_value = generate_json_value(&kind, &expr);
+
+        let mut json_stmt = quote::quote! {
+            if !__first {
+                w.write_str(",")?;
+            }
+            __first = false;
+            w.write_str(#json_key)?;
+            #json_value
+        };
+        let mut logfmt_stmt = generate_logfmt_pair(&kind, &expr, &logfmt_key);
+        if let Some(skip_if) = options.skip_if {
+            json_stmt = quote::quote! {
+                if !#skip_if(#binding) {
+                    #json_stmt
+                }
+            };
+            logfmt_stmt = quote::quote! {
+                if !#skip_if(#binding) {
+                    #logfmt_stmt
+                }
+            };
+        }
+        json_stmts.push(json_stmt);
+        logfmt_stmts.push(logfmt_stmt);
+    }
+
+    // entry的值是格式化字符串的输出，写成字符串
+    for entry in struct_options.entries.iter() {
+        uses_escape = true;
+        let name = entry.name.value();
+        check_logfmt_key(&name, Some(&entry.name))?;
+        let json_key = format!("\"{}\":", escape_log_str(&name));
+        let logfmt_key = format!("{}=", name);
+        let format_args =
+            generate_format_args(&parse_format_string(&entry.fmt, fields, false)?, None);
+        let json_value = generate_json_value(&LogKind::Str, &format_args);
+        json_stmts.push(quote::quote! {
+            if !__first {
+                w.write_str(",")?;
+            }
+            __first = false;
+            w.write_str(#json_key)?;
+            #json_value
+        });
+        logfmt_stmts.push(generate_logfmt_pair(
+            &LogKind::Str,
+            &format_args,
+            &logfmt_key,
+        ));
+    }
+
+    // 把写入的内容按JSON字符串的规则转义，logfmt中带引号的值也使用同样的规则
+    if uses_escape {
+        helper_defs.extend(quote::quote! {
+            struct __LogEscape<'a, W: ?Sized>(&'a mut W);
+
+            impl<'a, W: ::core::fmt::Write + ?Sized> ::core::fmt::Write for __LogEscape<'a, W> {
+                fn write_str(&mut self, s: &::core::primitive::str) -> ::core::fmt::Result {
+                    for c in s.chars() {
+                        match c {
+                            '"' => self.0.write_str("\\\"")?,
+                            '\\' => self.0.write_str("\\\\")?,
+                            '\n' => self.0.write_str("\\n")?,
+                            '\r' => self.0.write_str("\\r")?,
+                            '\t' => self.0.write_str("\\t")?,
+                            c if (c as ::core::primitive::u32) < 0x20 => {
+                                ::core::write!(self.0, "\\u{:04x}", c as ::core::primitive::u32)?
+                            }
+                            c => self.0.write_char(c)?,
+                        }
+                    }
+                    ::core::result::Result::Ok(())
+                }
+            }
+        });
+    }
+
+    let vis = &st.vis;
+    let pattern = generate_fields_pattern(fields);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let ret_stream = quote::quote! {
+        impl #impl_generics #struct_name_ident #ty_generics #where_clause {
+            /// Writes the fields as a single-line JSON object, in the same form as the `Debug` output.
+            #vis fn write_json<W: ::core::fmt::Write + ?Sized>(
+                &self,
+                w: &mut W,
+            ) -> ::core::fmt::Result {
+                #helper_defs
+                let #struct_name_ident #pattern = self;
+                let mut __first = true;
+                w.write_str("{")?;
+                #(#json_stmts)*
+                w.write_str("}")
+            }
+
+            /// Writes the fields as a single logfmt line of `key=value` pairs, in the same form as the `Debug` output.
+            #vis fn write_logfmt<W: ::core::fmt::Write + ?Sized>(
+                &self,
+                w: &mut W,
+            ) -> ::core::fmt::Result {
+                #helper_defs
+                let #struct_name_ident #pattern = self;
+                let mut __first = true;
+                #(#logfmt_stmts)*
+                ::core::result::Result::Ok(())
+            }
+        }
+    };
+
+    Ok(ret_stream)
 }
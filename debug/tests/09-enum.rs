@@ -0,0 +1,48 @@
+// Enums are supported as well. Each variant is rendered the same way the
+// standard library's derive would render it: unit variants as their bare
+// name, tuple variants through debug_tuple and struct variants through
+// debug_struct.
+//
+// Per-field #[debug = "..."] format strings work inside variants, and bounds
+// are inferred from the fields of all variants together.
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+#[derive(CustomDebug)]
+pub enum Packet<T> {
+    Ping,
+    Data(#[debug = "0x{:02x}"] u8, T),
+    Ack {
+        seq: u32,
+        #[debug = "0b{:04b}"]
+        flags: u8,
+    },
+}
+
+#[derive(CustomDebug)]
+pub enum Marker<T> {
+    Empty(PhantomData<T>),
+}
+
+#[derive(CustomDebug)]
+pub enum Never {}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    assert_eq!(format!("{:?}", Packet::<u8>::Ping), "Ping");
+    assert_eq!(
+        format!("{:?}", Packet::Data(255, "payload")),
+        r#"Data(0xff, "payload")"#
+    );
+    assert_eq!(
+        format!("{:?}", Packet::<()>::Ack { seq: 7, flags: 5 }),
+        "Ack { seq: 7, flags: 0b0101 }"
+    );
+
+    struct NotDebug;
+    assert_debug::<Marker<NotDebug>>();
+    assert_debug::<Never>();
+}
@@ -0,0 +1,24 @@
+// Tuple structs and unit structs are rendered like the standard library's
+// derive renders them: newtypes and other tuple structs through debug_tuple,
+// unit structs as their bare name.
+//
+// The #[debug = "..."] attribute works on positional fields too, so a newtype
+// around an integer id can for example be printed in hex.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct UserId(#[debug = "{:#x}"] u64);
+
+#[derive(CustomDebug)]
+pub struct Pair<T>(T, &'static str);
+
+#[derive(CustomDebug)]
+pub struct Unit;
+
+fn main() {
+    assert_eq!(format!("{:?}", UserId(255)), "UserId(0xff)");
+    assert_eq!(format!("{:?}", Pair(1, "one")), r#"Pair(1, "one")"#);
+    assert_eq!(format!("{:?}", Unit), "Unit");
+    assert_eq!(format!("{:#?}", UserId(16)), "UserId(\n    0x10,\n)");
+}
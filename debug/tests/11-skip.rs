@@ -0,0 +1,58 @@
+// Large caches and internal handles only add noise to Debug output. A field
+// marked #[debug(skip)] is left out of the output entirely, and since it is
+// never formatted its type does not contribute any inferred bound either.
+//
+// #[debug(skip_if = "path")] decides at runtime instead: the function is called
+// with a reference to the field and the field is omitted whenever it returns
+// true.
+//
+//     #[debug(skip_if = "Option::is_none")]
+//     parent: Option<u32>,
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+
+#[derive(CustomDebug)]
+pub struct Node<C> {
+    name: &'static str,
+    #[debug(skip)]
+    cache: C,
+    #[debug(skip_if = "Option::is_none")]
+    parent: Option<u32>,
+    #[debug(skip_if = "Vec::is_empty")]
+    children: Vec<u32>,
+}
+
+#[derive(CustomDebug)]
+pub struct Handle(u32, #[debug(skip)] std::fs::File);
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    struct NotDebug;
+    assert_debug::<Node<NotDebug>>();
+
+    let node = Node {
+        name: "root",
+        cache: NotDebug,
+        parent: None,
+        children: vec![1, 2],
+    };
+    assert_eq!(
+        format!("{:?}", node),
+        r#"Node { name: "root", children: [1, 2] }"#
+    );
+
+    let node = Node {
+        name: "leaf",
+        cache: NotDebug,
+        parent: Some(0),
+        children: vec![],
+    };
+    assert_eq!(
+        format!("{:?}", node),
+        r#"Node { name: "leaf", parent: Some(0) }"#
+    );
+
+    assert_debug::<Handle>();
+}
@@ -0,0 +1,50 @@
+// Secrets such as API tokens and passwords must never end up in logs through a
+// derived Debug impl. A field marked #[debug(redact)] prints a fixed
+// placeholder instead of its value:
+//
+//     #[debug(redact)]                 => <redacted>
+//     #[debug(redact = "***")]         => ***
+//     #[debug(redact(len))]            => <redacted 32 bytes>
+//
+// The value is never formatted, so the field type does not need to implement
+// Debug and no bound is inferred from it. The len form only requires the field
+// to have a len() method.
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+
+pub struct Token<T>(T);
+
+#[derive(CustomDebug)]
+pub struct Credentials<T> {
+    user: &'static str,
+    #[debug(redact)]
+    password: String,
+    #[debug(redact = "***")]
+    token: Token<T>,
+    #[debug(redact(len))]
+    key: Vec<u8>,
+}
+
+#[derive(CustomDebug)]
+pub struct Secret(#[debug(redact)] Token<u8>);
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    struct NotDebug;
+    assert_debug::<Credentials<NotDebug>>();
+
+    let credentials = Credentials {
+        user: "admin",
+        password: "hunter2".to_owned(),
+        token: Token(NotDebug),
+        key: vec![0; 32],
+    };
+    assert_eq!(
+        format!("{:?}", credentials),
+        r#"Credentials { user: "admin", password: <redacted>, token: ***, key: <redacted 32 bytes> }"#
+    );
+
+    assert_eq!(format!("{:?}", Secret(Token(1))), "Secret(<redacted>)");
+}
@@ -0,0 +1,61 @@
+// Foreign types that do not implement Debug, or values that need structured
+// output beyond what a format string can express, can be printed by a custom
+// formatter function:
+//
+//     #[debug(with = "fmt_duration")]
+//     timeout: Duration,
+//
+// The function is called as `fmt_duration(&self.timeout, f)` and must have the
+// signature fn(&T, &mut fmt::Formatter) -> fmt::Result. Since the field's own
+// Debug impl is never used, no bound is inferred from its type.
+
+use derive_debug::CustomDebug;
+use std::fmt::{self, Debug};
+use std::time::Duration;
+
+fn fmt_duration(d: &Duration, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}ms", d.as_millis())
+}
+
+mod ffi {
+    use std::fmt;
+
+    pub struct RawHandle(pub i32);
+
+    pub fn fmt_handle(h: &RawHandle, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("fd").field(&h.0).finish()
+    }
+}
+
+pub struct Opaque<T>(T);
+
+fn fmt_opaque<T>(_: &Opaque<T>, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str("<opaque>")
+}
+
+#[derive(CustomDebug)]
+pub struct Connection<T> {
+    #[debug(with = "fmt_duration")]
+    timeout: Duration,
+    #[debug(with = "ffi::fmt_handle")]
+    handle: ffi::RawHandle,
+    #[debug(with = "fmt_opaque")]
+    state: Opaque<T>,
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    struct NotDebug;
+    assert_debug::<Connection<NotDebug>>();
+
+    let connection = Connection {
+        timeout: Duration::from_millis(1500),
+        handle: ffi::RawHandle(3),
+        state: Opaque(NotDebug),
+    };
+    assert_eq!(
+        format!("{:?}", connection),
+        "Connection { timeout: 1500ms, handle: fd(3), state: <opaque> }"
+    );
+}
@@ -0,0 +1,82 @@
+// The escape hatch from 08 accepts any number of predicates: several
+// #[debug(bound = "...")] attributes on the struct, several bound entries in
+// one attribute, or comma-separated predicates in one string are all combined
+// into the where-clause.
+//
+// A bound on an individual field replaces only the bounds that would have been
+// inferred from that field's type, leaving inference for the other fields in
+// place:
+//
+//     #[derive(CustomDebug)]
+//     pub struct Wrapper<T: Trait, U> {
+//         #[debug(bound = "T::Value: Debug")]
+//         field: Field<T>,
+//         normal: U,
+//     }
+//
+// generates `impl<T: Trait, U: Debug> Debug for Wrapper<T, U> where T::Value: Debug`.
+// An empty string means the field needs no bounds at all.
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+
+pub trait Trait {
+    type Value;
+}
+
+#[derive(CustomDebug)]
+pub struct Field<T: Trait> {
+    values: Vec<T::Value>,
+}
+
+#[derive(CustomDebug)]
+pub struct Wrapper<T: Trait, U> {
+    #[debug(bound = "T::Value: Debug")]
+    field: Field<T>,
+    normal: U,
+}
+
+#[derive(CustomDebug)]
+#[debug(bound = "T::Value: Debug")]
+#[debug(bound = "U: Debug, V: Debug", bound = "W: Debug")]
+pub struct Many<T: Trait, U, V, W> {
+    field: Field<T>,
+    u: U,
+    v: Option<V>,
+    w: Vec<W>,
+}
+
+#[derive(CustomDebug)]
+pub struct Unbounded<T> {
+    #[debug(bound = "", with = "fmt_len")]
+    items: Vec<T>,
+}
+
+fn fmt_len<T>(items: &Vec<T>, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    write!(f, "{} items", items.len())
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    struct Id;
+
+    impl Trait for Id {
+        type Value = u8;
+    }
+
+    struct NotDebug;
+
+    assert_debug::<Wrapper<Id, u8>>();
+    assert_debug::<Many<Id, u8, u16, u32>>();
+    assert_debug::<Unbounded<NotDebug>>();
+
+    let wrapper = Wrapper::<Id, _> {
+        field: Field { values: vec![1, 2] },
+        normal: "n",
+    };
+    assert_eq!(
+        format!("{:?}", wrapper),
+        r#"Wrapper { field: Field { values: [1, 2] }, normal: "n" }"#
+    );
+}
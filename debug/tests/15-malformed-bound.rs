@@ -0,0 +1,18 @@
+// A bound that is not a valid where-predicate is reported as an error pointing
+// at the offending string, rather than panicking inside the macro.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(bound = "T: Debug, 42")]
+pub struct Wrapper<T> {
+    value: T,
+}
+
+#[derive(CustomDebug)]
+pub struct Field<T> {
+    #[debug(bound = "T Debug")]
+    value: T,
+}
+
+fn main() {}
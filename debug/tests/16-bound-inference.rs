@@ -0,0 +1,89 @@
+// Bounds are inferred from exactly where each type parameter appears in the
+// field types, however the type is spelled:
+//
+//   - a field type built only from type parameters and standard library types,
+//     like `&'a T`, `[T; N]`, `Box<T>` or `Sender<T>`, gets a `FieldType: Debug`
+//     predicate, so `Sender<T>` is Debug even when `T` is not;
+//   - a field type that also mentions another generic type, like `Vec<Tree<T>>`,
+//     requires `T: Debug` instead, because a `Vec<Tree<T>>: Debug` predicate
+//     on `Tree<T>` itself would be cyclic (see 06-bound-trouble.rs);
+//   - `T::Value` and `<T as Trait>::Value` require only the associated type to
+//     be Debug, not `T` itself;
+//   - `PhantomData<T>` under any path, function pointers and raw pointers
+//     implement Debug whatever `T` is, so they require nothing;
+//   - lifetimes and const generic parameters never need a bound.
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+use std::sync::mpsc::Receiver;
+
+pub trait Trait {
+    type Value;
+}
+
+#[derive(CustomDebug)]
+pub struct Markers<'a, A, B, C, D, E> {
+    a: std::marker::PhantomData<A>,
+    b: Vec<std::marker::PhantomData<&'a B>>,
+    c: fn() -> C,
+    d: fn(&D) -> bool,
+    e: *const E,
+}
+
+#[derive(CustomDebug)]
+pub struct Positions<'a, T, const N: usize> {
+    reference: &'a T,
+    array: [T; N],
+    boxed: Box<T>,
+    tuple: (u8, T),
+    slice: &'a [T],
+}
+
+#[derive(CustomDebug)]
+pub struct Associated<T: Trait, U: Trait> {
+    short: Vec<T::Value>,
+    qualified: Option<<U as Trait>::Value>,
+}
+
+#[derive(CustomDebug)]
+pub struct Worker<T> {
+    id: u32,
+    tx: std::sync::mpsc::Sender<T>,
+    results: Vec<Receiver<T>>,
+}
+
+#[derive(CustomDebug)]
+pub struct Tree<T> {
+    value: T,
+    children: Vec<Tree<T>>,
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    struct NotDebug;
+
+    impl Trait for NotDebug {
+        type Value = u8;
+    }
+
+    assert_debug::<Markers<NotDebug, NotDebug, NotDebug, NotDebug, NotDebug>>();
+    assert_debug::<Positions<u8, 4>>();
+    assert_debug::<Associated<NotDebug, NotDebug>>();
+    assert_debug::<Worker<NotDebug>>();
+    assert_debug::<Tree<u8>>();
+
+    let value = 1u8;
+    let items = [2u8];
+    let positions = Positions {
+        reference: &value,
+        array: [3u8; 2],
+        boxed: Box::new(4),
+        tuple: (5, 6),
+        slice: &items,
+    };
+    assert_eq!(
+        format!("{:?}", positions),
+        "Positions { reference: 1, array: [3, 3], boxed: 4, tuple: (5, 6), slice: [2] }"
+    );
+}
@@ -0,0 +1,74 @@
+// Debug output often ends up in logs shown to customers, where internal type
+// and field names should not leak.
+//
+// #[debug(name = "...")] on the struct or on an enum variant and
+// #[debug(rename = "...")] on a named field replace the identifiers that are
+// printed.
+//
+// #[debug(non_exhaustive)] on a struct or enum ends the output with `..`, like
+// Formatter::debug_struct(..).finish_non_exhaustive(), whenever a field was
+// left out, either statically by #[debug(skip)] or at runtime by
+// #[debug(skip_if = "...")]. #[debug(non_exhaustive(always))] always prints the
+// `..`.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(name = "Geometry", non_exhaustive)]
+pub struct InternalGeometryV2 {
+    #[debug(rename = "w")]
+    width: u32,
+    #[debug(rename = "h")]
+    height: u32,
+    #[debug(skip_if = "Option::is_none")]
+    label: Option<&'static str>,
+}
+
+#[derive(CustomDebug)]
+#[debug(non_exhaustive)]
+pub struct Session {
+    user: &'static str,
+    #[debug(skip)]
+    token: u64,
+}
+
+#[derive(CustomDebug)]
+#[debug(non_exhaustive(always))]
+pub enum Shape {
+    #[debug(name = "Dot")]
+    Point,
+    Circle { r: u32 },
+    Pair(u32, u32),
+}
+
+fn main() {
+    let geometry = InternalGeometryV2 {
+        width: 2,
+        height: 3,
+        label: None,
+    };
+    assert_eq!(format!("{:?}", geometry), "Geometry { w: 2, h: 3, .. }");
+
+    let geometry = InternalGeometryV2 {
+        width: 2,
+        height: 3,
+        label: Some("box"),
+    };
+    assert_eq!(
+        format!("{:?}", geometry),
+        r#"Geometry { w: 2, h: 3, label: Some("box") }"#
+    );
+
+    let session = Session {
+        user: "alice",
+        token: 42,
+    };
+    assert_eq!(format!("{:?}", session), r#"Session { user: "alice", .. }"#);
+
+    assert_eq!(format!("{:?}", Shape::Point), "Dot { .. }");
+    assert_eq!(
+        format!("{:?}", Shape::Circle { r: 1 }),
+        "Circle { r: 1, .. }"
+    );
+    assert_eq!(format!("{:?}", Shape::Pair(1, 2)), "Pair(1, 2, ..)");
+}
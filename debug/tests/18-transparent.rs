@@ -0,0 +1,65 @@
+// Newtypes are everywhere in a domain model, and printing `Meters(3.5)` for
+// each of them only adds noise. #[debug(transparent)] on a struct with a single
+// field delegates the whole Debug output to that field, so `Meters(3.5)` prints
+// as `3.5`.
+//
+// Fields marked #[debug(skip)] do not count, which allows marker fields such as
+// PhantomData next to the wrapped value. Field attributes like #[debug = "..."]
+// and #[debug(redact)] still apply to the wrapped field, and bounds are
+// inferred from the wrapped field's type only.
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Meters(f64);
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Name {
+    inner: String,
+}
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Id<T, U> {
+    #[debug = "#{:?}"]
+    value: T,
+    #[debug(skip)]
+    marker: PhantomData<U>,
+    #[debug(skip)]
+    cache: U,
+}
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Password(#[debug(redact)] String);
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    struct NotDebug;
+    assert_debug::<Id<u32, NotDebug>>();
+
+    assert_eq!(format!("{:?}", Meters(3.5)), "3.5");
+    assert_eq!(format!("{:.2?}", Meters(3.5)), "3.50");
+
+    let name = Name {
+        inner: "alice".to_owned(),
+    };
+    assert_eq!(format!("{:?}", name), r#""alice""#);
+
+    let id = Id {
+        value: 7u32,
+        marker: PhantomData,
+        cache: NotDebug,
+    };
+    assert_eq!(format!("{:?}", id), "#7");
+
+    assert_eq!(
+        format!("{:?}", Password("hunter2".to_owned())),
+        "<redacted>"
+    );
+}
@@ -0,0 +1,21 @@
+// #[debug(transparent)] needs exactly one field to delegate to. Structs with
+// several printed fields, and enums, are rejected with an error pointing at
+// the attribute.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub enum Either {
+    Left(i32),
+    Right(i32),
+}
+
+fn main() {}
@@ -0,0 +1,136 @@
+// Geometry and interval types read best as a compact one-liner. Format strings
+// given to CustomDebug may therefore refer to fields by name in addition to
+// the positional `{}` that stands for the annotated field itself:
+//
+//   - {name}, {self.name}, and {self.0} for tuple structs, with any format spec
+//     like {self.lo:?} or {width:.1};
+//   - on a field, #[debug = "..."] or the equivalent #[debug("...")] may mix
+//     the field's own value with its siblings;
+//   - on a struct, #[debug(entry(name = "...", fmt = "..."))] appends an entry
+//     that does not correspond to any field, which makes it possible to print
+//     one synthetic entry in place of several skipped fields. Entries also
+//     appear in debug_fields() and in the CustomLog output;
+//   - on a struct, #[debug(fmt = "...")] replaces the whole debug_struct output,
+//     and {0} refers to the first field of a tuple struct.
+//
+// Fields referenced from a format string are printed even if they are skipped
+// themselves, so their types take part in bound inference. Each placeholder
+// bounds its field by the trait its format spec uses: {lo} requires Display,
+// {lo:?} requires Debug, {:#x} requires LowerHex, and so on.
+
+use derive_debug::{CustomDebug, CustomLog};
+use std::fmt::Debug;
+
+#[derive(CustomDebug, CustomLog)]
+#[debug(reflect)]
+#[debug(entry(name = "range", fmt = "{self.lo:?}..{self.hi:?}"))]
+#[debug(entry(name = "len", fmt = "{len}"))]
+pub struct Interval<T> {
+    name: &'static str,
+    #[debug(skip)]
+    lo: T,
+    #[debug(skip)]
+    hi: T,
+    #[debug(skip)]
+    len: usize,
+}
+
+#[derive(CustomDebug)]
+pub struct Labeled {
+    #[debug = "{} (#{id})"]
+    label: &'static str,
+    #[debug(skip)]
+    id: u32,
+}
+
+#[derive(CustomDebug)]
+#[debug(fmt = "Range({lo}..{hi})")]
+pub struct Range {
+    lo: i64,
+    hi: i64,
+}
+
+#[derive(CustomDebug)]
+#[debug(fmt = "{{{0:.1}, {1:.1}}}")]
+pub struct Point(f64, f64);
+
+#[derive(CustomDebug)]
+#[debug(fmt = "{width:?}x{height:?}")]
+pub struct Size<W, H> {
+    width: W,
+    height: H,
+}
+
+#[derive(CustomDebug)]
+#[debug(fmt = "Span({lo}..{hi})")]
+pub struct Span<T> {
+    lo: T,
+    hi: T,
+}
+
+#[derive(CustomDebug)]
+pub struct Register<T, U> {
+    #[debug("{:#x}")]
+    bits: T,
+    #[debug("{} ({self.bits:b})")]
+    label: U,
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    assert_debug::<Interval<u8>>();
+    assert_debug::<Size<u8, u8>>();
+
+    let interval = Interval {
+        name: "week",
+        lo: 1u8,
+        hi: 7u8,
+        len: 7,
+    };
+    assert_eq!(
+        format!("{:?}", interval),
+        r#"Interval { name: "week", range: 1..7, len: 7 }"#
+    );
+    let entries: Vec<_> = interval
+        .debug_fields()
+        .map(|(name, value)| format!("{}={:?}", name, value))
+        .collect();
+    assert_eq!(entries, [r#"name="week""#, "range=1..7", "len=7"]);
+    let mut json = String::new();
+    interval.write_json(&mut json).unwrap();
+    assert_eq!(json, r#"{"name":"week","range":"1..7","len":"7"}"#);
+
+    let labeled = Labeled {
+        label: "root",
+        id: 3,
+    };
+    assert_eq!(format!("{:?}", labeled), "Labeled { label: root (#3) }");
+
+    assert_eq!(format!("{:?}", Range { lo: -2, hi: 5 }), "Range(-2..5)");
+    assert_eq!(format!("{:?}", Point(1.0, 2.25)), "{1.0, 2.2}");
+
+    let size = Size {
+        width: 3u8,
+        height: Some(4u8),
+    };
+    assert_eq!(format!("{:?}", size), "3xSome(4)");
+
+    struct DisplayOnly;
+
+    impl std::fmt::Display for DisplayOnly {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("status")
+        }
+    }
+
+    assert_eq!(format!("{:?}", Span { lo: 2, hi: 9 }), "Span(2..9)");
+    let register = Register {
+        bits: 5u8,
+        label: DisplayOnly,
+    };
+    assert_eq!(
+        format!("{:?}", register),
+        "Register { bits: 0x5, label: status (101) }"
+    );
+}
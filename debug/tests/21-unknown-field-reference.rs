@@ -0,0 +1,20 @@
+// A format string that refers to a field which does not exist is rejected at
+// compile time with an error pointing at the string.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(fmt = "Range({lo}..{high})")]
+pub struct Range {
+    lo: i64,
+    hi: i64,
+}
+
+#[derive(CustomDebug)]
+pub struct Labeled {
+    #[debug("{} (#{self.identifier})")]
+    label: &'static str,
+    id: u32,
+}
+
+fn main() {}
@@ -0,0 +1,83 @@
+// Logging a packet with thousands of bytes prints every byte in decimal, one
+// per line under {:#?}. Fields can opt into more compact renderings:
+//
+//   - #[debug(limit = N)] on any field whose reference is iterable prints the
+//     first N elements followed by the number of elements left out, e.g.
+//     `[1, 2, 3, ... (1021 more)]`. References and boxes are looked through,
+//     so `&[T]`, `&Vec<T>` and `Box<[T]>` fields work too;
+//   - #[debug(hex)] on a byte buffer (anything implementing AsRef<[u8]>, like
+//     Vec<u8>, [u8; N] or bytes::Bytes) prints the bytes as one lowercase hex
+//     string, e.g. `deadbeef`;
+//   - #[debug(ascii)] prints the buffer as an escaped byte string literal, e.g.
+//     `b"GET / HTTP/1.1\r\n"`.
+//
+// limit may be combined with hex or ascii to cut long buffers short.
+
+use derive_debug::CustomDebug;
+use std::collections::BTreeMap;
+
+#[derive(CustomDebug)]
+pub struct Packet {
+    #[debug(ascii)]
+    request_line: Vec<u8>,
+    #[debug(hex)]
+    magic: [u8; 4],
+    #[debug(hex, limit = 4)]
+    payload: Vec<u8>,
+    #[debug(limit = 3)]
+    sequence: Vec<u32>,
+    #[debug(limit = 1)]
+    headers: BTreeMap<&'static str, &'static str>,
+}
+
+#[derive(CustomDebug)]
+pub struct Samples<T>(#[debug(limit = 2)] Vec<T>);
+
+#[derive(CustomDebug)]
+pub struct Window<'a, T> {
+    #[debug(limit = 2)]
+    items: &'a [T],
+    #[debug(limit = 1)]
+    owner: &'a Vec<u32>,
+    #[debug(limit = 2)]
+    boxed: Box<[u8]>,
+}
+
+fn main() {
+    let mut headers = BTreeMap::new();
+    headers.insert("Host", "example.com");
+    headers.insert("Accept", "*/*");
+
+    let packet = Packet {
+        request_line: b"GET / HTTP/1.1\r\n".to_vec(),
+        magic: [0xde, 0xad, 0xbe, 0xef],
+        payload: (0..1024).map(|i| i as u8).collect(),
+        sequence: (1..=1024).collect(),
+        headers,
+    };
+    assert_eq!(
+        format!("{:?}", packet),
+        r#"Packet { request_line: b"GET / HTTP/1.1\r\n", magic: deadbeef, payload: 00010203... (1020 more), sequence: [1, 2, 3, ... (1021 more)], headers: [("Accept", "*/*"), ... (1 more)] }"#
+    );
+
+    assert_eq!(
+        format!("{:?}", Samples(vec![1.5, 2.5])),
+        "Samples([1.5, 2.5])"
+    );
+    assert_eq!(
+        format!("{:?}", Samples(vec!['a', 'b', 'c'])),
+        "Samples(['a', 'b', ... (1 more)])"
+    );
+
+    let items = [10u32, 20, 30];
+    let owner = vec![1, 2];
+    let window = Window {
+        items: &items,
+        owner: &owner,
+        boxed: vec![7, 8, 9].into_boxed_slice(),
+    };
+    assert_eq!(
+        format!("{:?}", window),
+        "Window { items: [10, 20, ... (1 more)], owner: [1, ... (1 more)], boxed: [7, 8, ... (1 more)] }"
+    );
+}
@@ -0,0 +1,90 @@
+// Error and ID types need a Display impl that is usually a one-line template.
+// #[derive(CustomDisplay)] generates it from #[display("...")] on the struct,
+// or on each variant of an enum. Placeholders name fields: {name}, {self.name}
+// or {0} for tuple fields, with any format spec such as {id:08} or {code:#x}.
+// A placeholder that does not name a field is a compile error.
+//
+// Bounds are inferred like for CustomDebug, but for the formatting trait each
+// placeholder actually uses: `{value}` requires `T: Display`, `{value:?}`
+// requires `T: Debug`, `{value:x}` requires `T: LowerHex`, and fields that are
+// not mentioned in the template or only hold PhantomData require nothing.
+// #[display("...", bound = "...")] replaces the inferred bounds.
+
+use derive_debug::CustomDisplay;
+use std::fmt::Display;
+use std::marker::PhantomData;
+
+#[derive(CustomDisplay)]
+#[display("{name} <{email}>")]
+pub struct Contact {
+    name: String,
+    email: String,
+}
+
+#[derive(CustomDisplay)]
+#[display("user-{0:06}")]
+pub struct UserId(u32);
+
+#[derive(CustomDisplay)]
+pub enum Error {
+    #[display("not found: {self.0}")]
+    NotFound(String),
+    #[display("http {status} ({code:#x}): {detail:?}")]
+    Http {
+        status: u16,
+        code: u32,
+        detail: Option<String>,
+    },
+    #[display("timed out")]
+    Timeout,
+}
+
+#[derive(CustomDisplay)]
+#[display("{id}: {payload:?}")]
+pub struct Tagged<I, P, M> {
+    id: I,
+    payload: P,
+    marker: PhantomData<M>,
+}
+
+#[derive(CustomDisplay)]
+#[display("{0}", bound = "T: Display + Clone")]
+pub struct Wrapper<T>(T);
+
+fn assert_display<F: Display>() {}
+
+fn main() {
+    struct Opaque;
+    #[derive(Debug)]
+    struct DebugOnly;
+
+    let contact = Contact {
+        name: "Alice".to_owned(),
+        email: "alice@example.com".to_owned(),
+    };
+    assert_eq!(contact.to_string(), "Alice <alice@example.com>");
+    assert_eq!(UserId(42).to_string(), "user-000042");
+
+    assert_eq!(
+        Error::NotFound("/index.html".to_owned()).to_string(),
+        "not found: /index.html"
+    );
+    let error = Error::Http {
+        status: 503,
+        code: 255,
+        detail: None,
+    };
+    assert_eq!(error.to_string(), "http 503 (0xff): None");
+    assert_eq!(Error::Timeout.to_string(), "timed out");
+
+    // `I` is printed with Display and `P` with Debug, `M` only in PhantomData
+    assert_display::<Tagged<u8, DebugOnly, Opaque>>();
+    let tagged: Tagged<_, _, Opaque> = Tagged {
+        id: 7,
+        payload: DebugOnly,
+        marker: PhantomData,
+    };
+    assert_eq!(tagged.to_string(), "7: DebugOnly");
+
+    assert_eq!(Wrapper(5).to_string(), "5");
+}
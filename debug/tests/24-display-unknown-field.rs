@@ -0,0 +1,19 @@
+// Every placeholder in a #[display("...")] template has to name a field of the
+// struct or variant it is attached to.
+
+use derive_debug::CustomDisplay;
+
+#[derive(CustomDisplay)]
+#[display("{name} <{mail}>")]
+pub struct Contact {
+    name: String,
+    email: String,
+}
+
+#[derive(CustomDisplay)]
+pub enum Error {
+    #[display("not found: {}")]
+    NotFound(String),
+}
+
+fn main() {}
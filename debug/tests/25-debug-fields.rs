@@ -0,0 +1,81 @@
+// A structured logger wants to emit each field as its own key instead of one
+// opaque Debug string. #[debug(reflect)] additionally generates an inherent
+//
+//     fn debug_fields(&self) -> impl Iterator<Item = (&'static str, Box<dyn Debug + '_>)>
+//
+// that yields the name and value of every field exactly as the Debug output
+// would print it: skipped fields are left out, renamed fields use their new
+// name, redacted fields yield the placeholder and custom formats apply.
+// Tuple fields are named by their index, and for an enum the fields of the
+// current variant are returned. The method has the visibility of the type.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(reflect)]
+pub struct Request<B> {
+    #[debug(rename = "http.method")]
+    method: &'static str,
+    #[debug = "/{}"]
+    path: &'static str,
+    #[debug(redact)]
+    token: String,
+    #[debug(skip)]
+    cache: Vec<u8>,
+    #[debug(skip_if = "Option::is_none")]
+    trace: Option<u64>,
+    body: B,
+}
+
+#[derive(CustomDebug)]
+#[debug(reflect, fmt = "{0}x{1}")]
+pub struct Size(u32, u32);
+
+#[derive(CustomDebug)]
+#[debug(reflect)]
+pub enum Event {
+    Started,
+    Progress { done: u32, total: u32 },
+    Failed(#[debug(hex)] Vec<u8>),
+}
+
+fn collect<'a>(
+    fields: impl Iterator<Item = (&'static str, Box<dyn std::fmt::Debug + 'a>)>,
+) -> String {
+    fields
+        .map(|(name, value)| format!("{}={:?}", name, value))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn main() {
+    let request = Request {
+        method: "GET",
+        path: "index.html",
+        token: "hunter2".to_owned(),
+        cache: Vec::new(),
+        trace: None,
+        body: [1, 2],
+    };
+    assert_eq!(
+        collect(request.debug_fields()),
+        r#"http.method="GET" path=/index.html token=<redacted> body=[1, 2]"#
+    );
+
+    assert_eq!(format!("{:?}", Size(3, 4)), "3x4");
+    assert_eq!(collect(Size(3, 4).debug_fields()), "0=3 1=4");
+
+    assert_eq!(collect(Event::Started.debug_fields()), "");
+    assert_eq!(
+        collect(Event::Progress { done: 1, total: 2 }.debug_fields()),
+        "done=1 total=2"
+    );
+    assert_eq!(
+        collect(Event::Failed(vec![0xca, 0xfe]).debug_fields()),
+        "0=cafe"
+    );
+
+    // the values borrow from the original, and keep the caller's format spec
+    let (_, body) = request.debug_fields().last().unwrap();
+    assert_eq!(format!("{:#?}", body), "[\n    1,\n    2,\n]");
+}
@@ -0,0 +1,104 @@
+// The generated impls refer to everything through absolute ::core paths,
+// including the Debug/Display bounds inferred for type parameters and the
+// format_args!/write! macros. This lets CustomDebug and CustomDisplay be used in
+// #![no_std] crates, and keeps them working when the caller's code redefines
+// prelude names or declares its own `mod core`.
+//
+// Only the optional debug_fields() method of #[debug(reflect)] allocates and
+// therefore still needs std.
+//
+// This test links std only to provide a panic handler for the binary; the
+// `std` name itself is not in scope.
+
+#![no_std]
+
+extern crate std as _;
+
+use ::core::fmt::Write;
+use derive_debug::{CustomDebug, CustomDisplay};
+
+#[allow(dead_code)]
+mod core {}
+
+#[allow(dead_code)]
+type Option = ();
+#[allow(dead_code)]
+type Result = ();
+#[allow(dead_code)]
+type Ok = ();
+
+#[allow(unused_macros)]
+macro_rules! format_args {
+    ($($tt:tt)*) => {
+        compile_error!("the local format_args! must not be used")
+    };
+}
+
+#[allow(unused_macros)]
+macro_rules! write {
+    ($($tt:tt)*) => {
+        compile_error!("the local write! must not be used")
+    };
+}
+
+#[derive(CustomDebug, CustomDisplay)]
+#[display("{id}: {value:?}")]
+pub struct Reading<T> {
+    id: u8,
+    #[debug = "{:?}mV"]
+    value: T,
+    #[debug(hex, limit = 2)]
+    raw: [u8; 4],
+    #[debug(limit = 1)]
+    history: [T; 3],
+}
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Celsius(i16);
+
+struct Buffer {
+    bytes: [u8; 128],
+    len: usize,
+}
+
+impl Write for Buffer {
+    fn write_str(&mut self, s: &str) -> ::core::fmt::Result {
+        let end = self.len + s.len();
+        self.bytes[self.len..end].copy_from_slice(s.as_bytes());
+        self.len = end;
+        ::core::result::Result::Ok(())
+    }
+}
+
+impl Buffer {
+    fn as_str(&self) -> &str {
+        ::core::str::from_utf8(&self.bytes[..self.len]).unwrap()
+    }
+}
+
+fn main() {
+    let reading = Reading {
+        id: 3,
+        value: 1200,
+        raw: [0xde, 0xad, 0xbe, 0xef],
+        history: [1, 2, 3],
+    };
+
+    let mut buffer = Buffer {
+        bytes: [0; 128],
+        len: 0,
+    };
+    ::core::write!(buffer, "{:?}", reading).unwrap();
+    assert_eq!(
+        buffer.as_str(),
+        "Reading { id: 3, value: 1200mV, raw: dead... (2 more), history: [1, ... (2 more)] }"
+    );
+
+    let mut buffer = Buffer {
+        bytes: [0; 128],
+        len: 0,
+    };
+    ::core::write!(buffer, "{} {:?}", reading, Celsius(-4)).unwrap();
+    assert_eq!(buffer.as_str(), "3: 1200 -4");
+}
@@ -0,0 +1,106 @@
+// Log pipelines ingest structured records rather than Debug strings.
+// #[derive(CustomLog)] reads the same #[debug(...)] attributes as CustomDebug
+// and generates two inherent methods writing the fields to any fmt::Write:
+//
+//     fn write_json<W: fmt::Write + ?Sized>(&self, w: &mut W) -> fmt::Result
+//     fn write_logfmt<W: fmt::Write + ?Sized>(&self, w: &mut W) -> fmt::Result
+//
+// Skipped fields are left out, renamed fields use their new name and redacted
+// or custom formatted fields are written as they would appear in the Debug
+// output. Integers and booleans are written as bare values and strings as
+// escaped strings. An `Option` of such a type is written as its value or JSON
+// `null`, and is left out of logfmt when it is `None`. A `Vec`, array or slice
+// of them is written as a JSON array, quoted as a string in logfmt. Any other
+// field, including type parameters, is written as the string of its Debug
+// output. Bounds are inferred exactly like for CustomDebug.
+
+#![deny(warnings)]
+
+use derive_debug::CustomLog;
+
+#[derive(CustomLog)]
+pub struct Request<'a, B> {
+    #[debug(rename = "http.method")]
+    method: &'a str,
+    path: String,
+    status: u16,
+    cached: bool,
+    latency: f64,
+    #[debug(redact)]
+    token: String,
+    #[debug(skip)]
+    raw: Vec<u8>,
+    #[debug(skip_if = "Option::is_none")]
+    trace: Option<u64>,
+    #[debug(hex)]
+    digest: [u8; 2],
+    tags: Vec<&'a str>,
+    sizes: [u16; 2],
+    parent: Option<u32>,
+    body: B,
+}
+
+#[derive(CustomLog)]
+pub struct Pair(i32, #[debug = "{}%"] u8);
+
+#[derive(CustomLog)]
+pub struct Empty;
+
+fn main() {
+    let request = Request {
+        method: "GET",
+        path: "/search?q=\"rust\"\n".to_owned(),
+        status: 200,
+        cached: false,
+        latency: 1.5,
+        token: "hunter2".to_owned(),
+        raw: Vec::new(),
+        trace: None,
+        digest: [0xab, 0xcd],
+        tags: vec!["a \"b\"", "c"],
+        sizes: [1, 2],
+        parent: None,
+        body: Some(vec![1, 2]),
+    };
+
+    let mut json = String::new();
+    request.write_json(&mut json).unwrap();
+    assert_eq!(
+        json,
+        r#"{"http.method":"GET","path":"/search?q=\"rust\"\n","status":200,"cached":false,"latency":1.5,"token":"<redacted>","digest":"abcd","tags":["a \"b\"","c"],"sizes":[1,2],"parent":null,"body":"Some([1, 2])"}"#
+    );
+
+    let mut logfmt = String::new();
+    request.write_logfmt(&mut logfmt).unwrap();
+    assert_eq!(
+        logfmt,
+        r#"http.method="GET" path="/search?q=\"rust\"\n" status=200 cached=false latency=1.5 token="<redacted>" digest="abcd" tags="[\"a \\\"b\\\"\",\"c\"]" sizes="[1,2]" body="Some([1, 2])""#
+    );
+
+    let request = Request {
+        latency: f64::NAN,
+        trace: Some(7),
+        parent: Some(3),
+        ..request
+    };
+    let mut json = String::new();
+    request.write_json(&mut json).unwrap();
+    assert!(json.contains(r#""latency":null,"token":"<redacted>","trace":7,"#));
+    assert!(json.contains(r#""parent":3,"#));
+    let mut logfmt = String::new();
+    request.write_logfmt(&mut logfmt).unwrap();
+    assert!(logfmt.contains(r#" trace=7 "#));
+    assert!(logfmt.contains(r#" parent=3 "#));
+
+    let mut out = String::new();
+    Pair(-1, 50).write_json(&mut out).unwrap();
+    out.push(' ');
+    Pair(-1, 50).write_logfmt(&mut out).unwrap();
+    assert_eq!(out, r#"{"0":-1,"1":"50%"} 0=-1 1="50%""#);
+
+    let mut out = String::new();
+    let writer: &mut dyn std::fmt::Write = &mut out;
+    Empty.write_json(writer).unwrap();
+    Empty.write_logfmt(writer).unwrap();
+    assert_eq!(out, "{}");
+}
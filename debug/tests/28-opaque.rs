@@ -0,0 +1,86 @@
+// A union does not record which of its fields is active, so there is no safe
+// way to print a field. Instead of refusing to derive, CustomDebug prints the
+// type name and size of a union, so structs containing one can still derive
+// Debug:
+//
+//     Value { size: 8 }
+//
+// #[debug(opaque)] does the same for a struct or enum whose contents should
+// not be printed; no bounds are inferred since no field is formatted.
+//
+// #[debug(opaque(hex))] additionally prints every byte of the value in hex.
+// Reading the raw bytes is only sound if all of them are initialized, so it is
+// only accepted on non-generic structs and unions whose fields are integers,
+// floats, `bool`, `char` or arrays of them, and a compile-time assertion checks
+// that a struct has no padding and that every union field is as large as the
+// union (see 33-opaque-hex-uninit.rs).
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+
+#[derive(CustomDebug, Clone, Copy)]
+#[repr(C)]
+pub union Value {
+    int: u64,
+    float: f64,
+}
+
+#[derive(CustomDebug)]
+#[repr(C)]
+#[debug(opaque(hex), name = "Word")]
+pub union RawWord {
+    bytes: [u8; 4],
+    word: u32,
+}
+
+#[derive(CustomDebug)]
+#[repr(C)]
+#[debug(opaque(hex))]
+pub struct Header {
+    magic: [u8; 2],
+    version: u16,
+}
+
+#[derive(CustomDebug)]
+pub struct Slot {
+    tag: u8,
+    value: Value,
+    raw: RawWord,
+}
+
+#[derive(CustomDebug)]
+#[debug(opaque)]
+pub struct Handle<T> {
+    inner: T,
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    struct NotDebug;
+    assert_debug::<Handle<NotDebug>>();
+
+    let slot = Slot {
+        tag: 1,
+        value: Value { int: 7 },
+        raw: RawWord {
+            bytes: [0xde, 0xad, 0xbe, 0xef],
+        },
+    };
+    assert_eq!(
+        format!("{:?}", slot),
+        "Slot { tag: 1, value: Value { size: 8 }, raw: Word { size: 4, bytes: deadbeef } }"
+    );
+    let _ = unsafe { (slot.value.float, slot.raw.word) };
+
+    let header = Header {
+        magic: *b"PK",
+        version: 0x0102u16.to_be(),
+    };
+    assert_eq!(format!("{:?}", header), "Header { size: 4, bytes: 504b0102 }");
+    let _ = (header.magic, header.version);
+
+    let handle = Handle { inner: [0u16; 3] };
+    assert_eq!(format!("{:?}", handle), "Handle { size: 6 }");
+    let _ = handle.inner;
+}
@@ -0,0 +1,101 @@
+// Recursive data structures can make Debug output arbitrarily deep, and cyclic
+// ones make it recurse until the stack overflows.
+//
+// #[debug(max_depth = N)] on a type counts, per thread, how deeply values of
+// that type are nested inside each other while being printed. Past N levels
+// the value prints as `...` instead of its fields. The counter is thread-local,
+// so this option needs std.
+//
+// #[debug(ptr)] on a field prints it with fmt::Pointer instead of Debug, i.e.
+// the address an Rc, Arc, Box or reference points to, which identifies shared
+// nodes without recursing into them. An Option of such a pointer prints as
+// Some(0x...) or None. The pointee does not need to implement Debug.
+
+use derive_debug::CustomDebug;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[derive(CustomDebug)]
+#[debug(max_depth = 2)]
+pub struct List {
+    value: u32,
+    next: Option<Box<List>>,
+}
+
+#[derive(CustomDebug)]
+#[debug(max_depth = 1)]
+pub struct Cyclic {
+    id: u32,
+    next: RefCell<Option<Rc<Cyclic>>>,
+}
+
+pub struct Graph;
+
+#[derive(CustomDebug)]
+pub struct GraphNode {
+    id: u32,
+    #[debug(ptr)]
+    graph: Rc<Graph>,
+    #[debug(ptr)]
+    parent: Option<Rc<GraphNode>>,
+}
+
+fn main() {
+    let list = List {
+        value: 1,
+        next: Some(Box::new(List {
+            value: 2,
+            next: Some(Box::new(List {
+                value: 3,
+                next: None,
+            })),
+        })),
+    };
+    assert_eq!(
+        format!("{:?}", list),
+        "List { value: 1, next: Some(List { value: 2, next: Some(...) }) }"
+    );
+    // the depth is counted per print, not accumulated across calls
+    assert_eq!(
+        format!("{:?}", list.next.as_ref().unwrap()),
+        "List { value: 2, next: Some(List { value: 3, next: None }) }"
+    );
+
+    let a = Rc::new(Cyclic {
+        id: 1,
+        next: RefCell::new(None),
+    });
+    let b = Rc::new(Cyclic {
+        id: 2,
+        next: RefCell::new(Some(a.clone())),
+    });
+    *a.next.borrow_mut() = Some(b.clone());
+    assert_eq!(
+        format!("{:?}", a),
+        "Cyclic { id: 1, next: RefCell { value: Some(...) } }"
+    );
+    a.next.borrow_mut().take();
+
+    let graph = Rc::new(Graph);
+    let root = Rc::new(GraphNode {
+        id: 0,
+        graph: graph.clone(),
+        parent: None,
+    });
+    let child = GraphNode {
+        id: 1,
+        graph: graph.clone(),
+        parent: Some(root.clone()),
+    };
+    assert_eq!(
+        format!("{:?}", root),
+        format!("GraphNode {{ id: 0, graph: {:p}, parent: None }}", graph)
+    );
+    assert_eq!(
+        format!("{:?}", child),
+        format!(
+            "GraphNode {{ id: 1, graph: {:p}, parent: Some({:p}) }}",
+            graph, root
+        )
+    );
+}
@@ -0,0 +1,53 @@
+// Only `#[debug(...)]` and the legacy `#[debug = "..."]` are interpreted by the
+// derive. Doc comments and any other attributes on the type or its fields are
+// left alone, and options may be spread over several `#[debug(...)]`.
+
+use derive_debug::CustomDebug;
+
+/// A user account.
+#[derive(CustomDebug)]
+#[allow(dead_code)]
+#[debug(name = "Account")]
+#[debug(non_exhaustive)]
+pub struct User {
+    /// The login name.
+    #[debug = "<{}>"]
+    login: String,
+    /// Numeric id, printed in hex.
+    #[cfg_attr(test, allow(unused))]
+    #[debug(rename = "uid")]
+    #[debug("{:#x}")]
+    id: u32,
+    /// Never printed.
+    #[doc(hidden)]
+    #[debug(skip)]
+    password: String,
+}
+
+/// Tuple variants keep their doc comments too.
+#[derive(CustomDebug)]
+pub enum Event {
+    /// A key was pressed.
+    Key(
+        /// The key code.
+        #[debug = "{:?}!"]
+        char,
+    ),
+    /// The window was closed.
+    Close,
+}
+
+fn main() {
+    let user = User {
+        login: "ferris".to_string(),
+        id: 255,
+        password: "hunter2".to_string(),
+    };
+    assert_eq!(
+        format!("{:?}", user),
+        r#"Account { login: <ferris>, uid: 0xff, .. }"#
+    );
+
+    assert_eq!(format!("{:?}", Event::Key('q')), "Key('q'!)");
+    assert_eq!(format!("{:?}", Event::Close), "Close");
+}
@@ -0,0 +1,68 @@
+// Every key inside `#[debug(...)]` is validated. Misspelled keys are reported
+// at the key with a suggestion, keys used in the wrong place or with the wrong
+// shape say how to write them, and repeated keys are rejected. Enum variants
+// only accept `debug(name = "...")`.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Misspelled {
+    #[debug(skp)]
+    secret: String,
+}
+
+#[derive(CustomDebug)]
+#[debug(transparant)]
+pub struct Wrapper(u8);
+
+#[derive(CustomDebug)]
+pub struct Unknown {
+    #[debug(color = "red")]
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct WrongPlace {
+    #[debug(transparent)]
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+#[debug(skip)]
+pub struct WrongPlaceStruct {
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct WrongShape {
+    #[debug(limit = "3")]
+    values: Vec<u8>,
+}
+
+#[derive(CustomDebug)]
+pub struct Duplicate {
+    #[debug(rename = "a")]
+    #[debug(rename = "b")]
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+#[debug = "{value}"]
+pub struct LegacyOnStruct {
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+pub enum VariantOptions {
+    #[debug(skip)]
+    Hidden,
+    Shown,
+}
+
+#[derive(CustomDebug)]
+pub enum VariantTypo {
+    #[debug(nmae = "Other")]
+    Renamed,
+}
+
+fn main() {}
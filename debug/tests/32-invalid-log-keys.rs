@@ -0,0 +1,19 @@
+// logfmt keys are written without quotes, so CustomLog rejects renamed fields
+// and entries whose name is empty or contains whitespace, `=` or `"`. Such
+// names are fine for CustomDebug.
+
+use derive_debug::CustomLog;
+
+#[derive(CustomLog)]
+pub struct Renamed {
+    #[debug(rename = "a b=c")]
+    value: u8,
+}
+
+#[derive(CustomLog)]
+#[debug(entry(name = "", fmt = "{value}"))]
+pub struct Entry {
+    value: u8,
+}
+
+fn main() {}
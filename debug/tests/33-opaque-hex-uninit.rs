@@ -0,0 +1,56 @@
+// #[debug(opaque(hex))] reads every byte of the value, which is undefined
+// behavior if any of them is uninitialized. Padding bytes, the unused bytes of
+// a smaller union field, enum payloads and fields of arbitrary types, even
+// behind an alias named like a primitive, may all be uninitialized, so these
+// are rejected at compile time.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(opaque(hex))]
+pub struct Padded {
+    tag: u8,
+    value: u32,
+}
+
+#[derive(CustomDebug)]
+#[repr(C)]
+#[debug(opaque(hex))]
+pub union Uneven {
+    byte: u8,
+    word: u32,
+}
+
+#[derive(CustomDebug)]
+#[debug(opaque(hex))]
+pub struct Nested {
+    pair: (u8, u32),
+}
+
+mod shadowed {
+    use derive_debug::CustomDebug;
+
+    #[allow(non_camel_case_types)]
+    type u64 = (u8, u32);
+
+    #[derive(CustomDebug)]
+    #[debug(opaque(hex))]
+    pub struct Aliased {
+        value: u64,
+    }
+}
+
+#[derive(CustomDebug)]
+#[debug(opaque(hex))]
+pub struct Generic<T> {
+    value: T,
+}
+
+#[derive(CustomDebug)]
+#[debug(opaque(hex))]
+pub enum Tagged {
+    A(u8),
+    B(u32),
+}
+
+fn main() {}
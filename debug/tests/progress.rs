@@ -9,4 +9,29 @@ fn tests() {
     t.pass("tests/06-bound-trouble.rs");
     t.pass("tests/07-associated-type.rs");
     t.pass("tests/08-escape-hatch.rs");
+    t.pass("tests/09-enum.rs");
+    t.pass("tests/10-tuple-and-unit-struct.rs");
+    t.pass("tests/11-skip.rs");
+    t.pass("tests/12-redact.rs");
+    t.pass("tests/13-debug-with.rs");
+    t.pass("tests/14-multiple-bounds.rs");
+    t.compile_fail("tests/15-malformed-bound.rs");
+    t.pass("tests/16-bound-inference.rs");
+    t.pass("tests/17-rename-and-non-exhaustive.rs");
+    t.pass("tests/18-transparent.rs");
+    t.compile_fail("tests/19-transparent-multiple-fields.rs");
+    t.pass("tests/20-field-references.rs");
+    t.compile_fail("tests/21-unknown-field-reference.rs");
+    t.pass("tests/22-collections-and-bytes.rs");
+    t.pass("tests/23-custom-display.rs");
+    t.compile_fail("tests/24-display-unknown-field.rs");
+    t.pass("tests/25-debug-fields.rs");
+    t.pass("tests/26-no-std.rs");
+    t.pass("tests/27-custom-log.rs");
+    t.pass("tests/28-opaque.rs");
+    t.pass("tests/29-recursion.rs");
+    t.pass("tests/30-attribute-grammar.rs");
+    t.compile_fail("tests/31-invalid-options.rs");
+    t.compile_fail("tests/32-invalid-log-keys.rs");
+    t.compile_fail("tests/33-opaque-hex-uninit.rs");
 }